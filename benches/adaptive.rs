@@ -0,0 +1,77 @@
+//! Compares `AdaptiveBorrow::adaptive_borrow_mut` with the fixed budget of
+//! `AtomicBorrow::spin_borrow_mut`, for a range of thread counts and hold times.
+//!
+//! Run with `cargo bench --bench adaptive`. Short holds reward spinning, long holds
+//! with many threads reward giving up the CPU early, which is what the adaptive budget
+//! is meant to find on its own.
+
+#[cfg(not(feature = "single-threaded"))]
+fn main() {
+    use std::{
+        hint::black_box,
+        sync::Barrier,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use atomic_borrow::{AdaptiveBorrow, AtomicBorrow};
+
+    const ACQUISITIONS: usize = 1000;
+    const RUNS: usize = 5;
+
+    fn run(threads: usize, hold: usize, adaptive: bool) -> Duration {
+        let borrow = AdaptiveBorrow::new();
+        let barrier = Barrier::new(threads + 1);
+
+        let start = thread::scope(|s| {
+            for _ in 0..threads {
+                s.spawn(|| {
+                    barrier.wait();
+
+                    for _ in 0..ACQUISITIONS {
+                        if adaptive {
+                            borrow.adaptive_borrow_mut();
+                        } else {
+                            AtomicBorrow::spin_borrow_mut(&borrow);
+                        }
+
+                        for i in 0..hold {
+                            black_box(i);
+                        }
+
+                        borrow.release_mut();
+                    }
+                });
+            }
+
+            // taken before releasing the threads, which may run before this one does
+            let start = Instant::now();
+            barrier.wait();
+            start
+        });
+
+        start.elapsed()
+    }
+
+    println!(
+        "{:>7} {:>6} {:>12} {:>12}",
+        "threads", "hold", "fixed", "adaptive"
+    );
+
+    for threads in [1, 4, 16] {
+        for hold in [0, 64, 4096] {
+            let [fixed, adaptive] = [false, true].map(|adaptive| {
+                let mut times = (0..RUNS)
+                    .map(|_| run(threads, hold, adaptive))
+                    .collect::<Vec<_>>();
+                times.sort();
+                times[RUNS / 2]
+            });
+
+            println!("{threads:>7} {hold:>6} {fixed:>12.2?} {adaptive:>12.2?}");
+        }
+    }
+}
+
+#[cfg(feature = "single-threaded")]
+fn main() {}
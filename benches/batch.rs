@@ -0,0 +1,53 @@
+//! Compares releasing shared guards through a `GuardBatch` with dropping them one by one.
+//!
+//! Run with `cargo bench --bench batch`. A batch releases all of its guards with a single
+//! atomic operation, so the difference grows with the number of guards.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use atomic_borrow::{AtomicRefCell, SharedGuard};
+
+const ITERATIONS: usize = 1000;
+const RUNS: usize = 5;
+
+fn run(guards: usize, batch: bool) -> Duration {
+    let cell = AtomicRefCell::new(0u64);
+    let mut elapsed = Duration::ZERO;
+
+    for _ in 0..ITERATIONS {
+        let borrowed = (0..guards).map(|_| cell.borrow()).collect::<Vec<_>>();
+
+        // only the release is timed, acquiring is the same either way
+        if batch {
+            let batch = SharedGuard::into_batch(borrowed).ok().unwrap();
+
+            let start = Instant::now();
+            drop(black_box(batch));
+            elapsed += start.elapsed();
+        } else {
+            let start = Instant::now();
+            drop(black_box(borrowed));
+            elapsed += start.elapsed();
+        }
+    }
+
+    assert!(cell.try_borrow_mut().is_ok());
+    elapsed
+}
+
+fn main() {
+    println!("{:>6} {:>12} {:>12}", "guards", "individual", "batch");
+
+    for guards in [1, 8, 64, 512] {
+        let [individual, batch] = [false, true].map(|batch| {
+            let mut times = (0..RUNS).map(|_| run(guards, batch)).collect::<Vec<_>>();
+            times.sort();
+            times[RUNS / 2]
+        });
+
+        println!("{guards:>6} {individual:>12.2?} {batch:>12.2?}");
+    }
+}
@@ -0,0 +1,76 @@
+//! Compares `spin_borrow_mut` with `spin_borrow_mut_jittered` while 64 threads contend
+//! for the same borrow.
+//!
+//! Run with `cargo bench --bench jitter`. Without jitter every waiting thread retries as
+//! soon as the borrow is released, so more of the retries collide and fail.
+
+#[cfg(not(feature = "single-threaded"))]
+fn main() {
+    use std::{
+        hint::black_box,
+        sync::Barrier,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use atomic_borrow::AtomicBorrow;
+
+    const THREADS: usize = 64;
+    const ACQUISITIONS: usize = 1000;
+    const RUNS: usize = 5;
+
+    fn run(acquire: fn(&AtomicBorrow)) -> Duration {
+        let borrow = AtomicBorrow::new();
+        let barrier = Barrier::new(THREADS + 1);
+
+        // the scope only returns once every thread is done
+        let start = thread::scope(|s| {
+            for _ in 0..THREADS {
+                s.spawn(|| {
+                    barrier.wait();
+
+                    for _ in 0..ACQUISITIONS {
+                        acquire(&borrow);
+
+                        // a short critical section
+                        for i in 0..16 {
+                            black_box(i);
+                        }
+
+                        borrow.release_mut();
+                    }
+                });
+            }
+
+            // taken before releasing the threads, which may run before this one does
+            let start = Instant::now();
+            barrier.wait();
+            start
+        });
+
+        start.elapsed()
+    }
+
+    for (name, acquire) in [
+        (
+            "spin_borrow_mut",
+            AtomicBorrow::spin_borrow_mut as fn(&AtomicBorrow),
+        ),
+        (
+            "spin_borrow_mut_jittered",
+            AtomicBorrow::spin_borrow_mut_jittered,
+        ),
+    ] {
+        let mut times = (0..RUNS).map(|_| run(acquire)).collect::<Vec<_>>();
+        times.sort();
+
+        let per_acquisition = times[RUNS / 2] / (THREADS * ACQUISITIONS) as u32;
+        println!(
+            "{name:<26} median {:>10.2?}  {per_acquisition:>8.2?}/acquisition",
+            times[RUNS / 2]
+        );
+    }
+}
+
+#[cfg(feature = "single-threaded")]
+fn main() {}
@@ -0,0 +1,85 @@
+use std::{
+    ops::Deref,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::AtomicBorrow;
+
+/// An [`AtomicBorrow`] that adapts how long it spins to recent contention.
+#[derive(Debug)]
+pub struct AdaptiveBorrow {
+    borrow: AtomicBorrow,
+    spin_budget: AtomicUsize,
+}
+
+impl Default for AdaptiveBorrow {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AdaptiveBorrow {
+    const MIN_SPIN: usize = 1 << 4;
+    const MAX_SPIN: usize = 1 << 14;
+
+    const_fn! {
+        /// Creates a new `AdaptiveBorrow`.
+        #[inline]
+        pub fn new() -> Self {
+            Self {
+                borrow: AtomicBorrow::new(),
+                spin_budget: AtomicUsize::new(AtomicBorrow::SPIN_COUNT),
+            }
+        }
+    }
+
+    /// Returns the current number of spins before yielding.
+    #[inline]
+    pub fn spin_budget(&self) -> usize {
+        self.spin_budget.load(Ordering::Relaxed)
+    }
+
+    /// Spins until a unique reference can be acquired.
+    ///
+    /// When acquisitions succeed while spinning the spin budget grows, when they have
+    /// to fall back to yielding it shrinks, so long holds stop burning CPU time.
+    #[inline]
+    pub fn adaptive_borrow_mut(&self) {
+        let budget = self.spin_budget();
+
+        for spins in 0..budget {
+            if self.borrow.borrow_mut() {
+                if spins > 0 {
+                    self.adapt(budget, (budget * 2).min(Self::MAX_SPIN));
+                }
+
+                return;
+            }
+
+            std::hint::spin_loop();
+        }
+
+        self.adapt(budget, (budget / 2).max(Self::MIN_SPIN));
+
+        while !self.borrow.borrow_mut() {
+            std::thread::yield_now();
+        }
+    }
+
+    #[inline]
+    fn adapt(&self, budget: usize, target: usize) {
+        // moving average, racing updates only lose a sample
+        let new = (budget * 3 + target) / 4;
+        self.spin_budget.store(new, Ordering::Relaxed);
+    }
+}
+
+impl Deref for AdaptiveBorrow {
+    type Target = AtomicBorrow;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.borrow
+    }
+}
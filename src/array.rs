@@ -0,0 +1,201 @@
+use std::{ops::Index, sync::atomic::Ordering};
+
+use crate::{AtomicBorrow, SharedGuard};
+
+impl AtomicBorrow {
+    /// Tries to borrow all `datas`, each registered with the respective `borrows`,
+    /// without allocating.
+    ///
+    /// If any borrow fails, the already acquired ones are released and `None` is returned.
+    ///
+    /// # Safety
+    /// * Any borrows of each of `datas` must be registered with the respective `borrows`.
+    /// * Each of `datas` must be a valid pointer for the entire lifetime `'a`.
+    #[inline]
+    pub unsafe fn try_borrow_array<'a, const N: usize, T>(
+        borrows: &[&'a AtomicBorrow; N],
+        datas: &[*const T; N],
+    ) -> Option<[SharedGuard<'a, T>; N]> {
+        for (i, borrow) in borrows.iter().enumerate() {
+            if !borrow.borrow() {
+                for borrow in &borrows[..i] {
+                    borrow.release();
+                }
+
+                return None;
+            }
+        }
+
+        Some(std::array::from_fn(|i| SharedGuard {
+            data: datas[i],
+            borrow: borrows[i],
+        }))
+    }
+}
+
+/// A fixed size array of [`AtomicBorrow`]s, eg. for the slots of a slab.
+#[derive(Debug, Default)]
+pub struct AtomicBorrowArray {
+    borrows: Box<[AtomicBorrow]>,
+}
+
+/// Hints the CPU to load the cache line of `borrow`, which is a no-op on targets without
+/// a stable prefetch instruction.
+#[inline(always)]
+#[allow(unused_variables)]
+fn prefetch(borrow: &AtomicBorrow) {
+    #[cfg(all(target_arch = "x86_64", not(loom)))]
+    unsafe {
+        use std::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+
+        // SAFETY: prefetching never faults, and `sse` is part of the x86_64 baseline
+        _mm_prefetch::<_MM_HINT_T0>(borrow as *const AtomicBorrow as *const i8);
+    }
+}
+
+impl AtomicBorrowArray {
+    /// Slots are scanned in chunks of this size, which is one cache line on most targets.
+    const CHUNK_SIZE: usize = 64 / std::mem::size_of::<AtomicBorrow>();
+    /// How many chunks ahead of the scan are prefetched, enough to hide the memory
+    /// latency of loading them while scanning the ones in between.
+    const PREFETCH_CHUNKS: usize = 4;
+
+    /// Creates a new `AtomicBorrowArray` with `len` unborrowed slots.
+    #[inline]
+    pub fn new(len: usize) -> Self {
+        Self {
+            borrows: (0..len).map(|_| AtomicBorrow::new()).collect(),
+        }
+    }
+
+    /// Finds `count` slots that are currently not borrowed, in ascending order.
+    ///
+    /// The slots are only candidates, other threads may borrow them before the caller
+    /// does, so acquiring them can still fail. Returns `None` if there aren't enough
+    /// free slots.
+    #[inline]
+    pub fn find_free_slots(&self, count: usize) -> Option<Vec<usize>> {
+        let mut slots = Vec::with_capacity(count);
+
+        if count == 0 {
+            return Some(slots);
+        }
+
+        for (i, chunk) in self.borrows.chunks(Self::CHUNK_SIZE).enumerate() {
+            let ahead = (i + Self::PREFETCH_CHUNKS) * Self::CHUNK_SIZE;
+            if let Some(borrow) = self.borrows.get(ahead) {
+                prefetch(borrow);
+            }
+
+            // the results are only candidates anyway, so relaxed loads are enough
+            let free = |borrow: &AtomicBorrow| borrow.borrow.load(Ordering::Relaxed) == 0;
+
+            // skip full chunks with a single pass that doesn't branch per slot
+            if !chunk.iter().fold(false, |any, borrow| any | free(borrow)) {
+                continue;
+            }
+
+            for (j, borrow) in chunk.iter().enumerate() {
+                if free(borrow) {
+                    slots.push(i * Self::CHUNK_SIZE + j);
+
+                    if slots.len() == count {
+                        return Some(slots);
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl std::ops::Deref for AtomicBorrowArray {
+    type Target = [AtomicBorrow];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.borrows
+    }
+}
+
+/// An [`AtomicBorrow`] padded to a cache line of its own.
+#[derive(Debug, Default)]
+#[repr(align(64))]
+struct CachePadded(AtomicBorrow);
+
+/// A fixed size array of `N` [`AtomicBorrow`]s, each padded to its own cache line.
+///
+/// Slots borrowed by threads on different cores then don't invalidate each other's
+/// cache lines, at the cost of 64 bytes per slot. Prefer [`AtomicBorrowArray`] unless
+/// slots are contended by many cores.
+#[derive(Debug)]
+pub struct PaddedAtomicBorrowArray<const N: usize> {
+    borrows: [CachePadded; N],
+}
+
+impl<const N: usize> PaddedAtomicBorrowArray<N> {
+    /// The size each slot is padded to.
+    pub const PADDING: usize = std::mem::align_of::<CachePadded>();
+
+    /// Creates a new `PaddedAtomicBorrowArray` with unborrowed slots.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            borrows: std::array::from_fn(|_| CachePadded::default()),
+        }
+    }
+
+    /// Gets the [`AtomicBorrow`] of slot `index`, or `None` if out of bounds.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&AtomicBorrow> {
+        self.borrows.get(index).map(|padded| &padded.0)
+    }
+
+    /// Tries to acquire a shared reference to slot `index`.
+    ///
+    /// Returns `true` if the reference was acquired.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    #[inline]
+    #[track_caller]
+    pub fn try_borrow(&self, index: usize) -> bool {
+        self[index].borrow()
+    }
+
+    /// Tries to acquire a unique reference to slot `index`.
+    ///
+    /// Returns `true` if the reference was acquired.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    #[inline]
+    #[track_caller]
+    pub fn try_borrow_mut(&self, index: usize) -> bool {
+        self[index].borrow_mut()
+    }
+
+    /// Returns an iterator over the [`AtomicBorrow`]s of the slots.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &AtomicBorrow> {
+        self.borrows.iter().map(|padded| &padded.0)
+    }
+}
+
+impl<const N: usize> Default for PaddedAtomicBorrowArray<N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Index<usize> for PaddedAtomicBorrowArray<N> {
+    type Output = AtomicBorrow;
+
+    #[inline]
+    #[track_caller]
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.borrows[index].0
+    }
+}
@@ -0,0 +1,49 @@
+use std::{
+    backtrace::Backtrace,
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+};
+
+use crate::AtomicBorrow;
+
+/// Backtraces of where unique borrows were acquired, keyed by the address of the
+/// [`AtomicBorrow`].
+fn registry() -> &'static Mutex<HashMap<usize, Backtrace>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<usize, Backtrace>>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+impl AtomicBorrow {
+    #[inline]
+    fn backtrace_key(&self) -> usize {
+        self as *const Self as usize
+    }
+
+    /// Records a backtrace of the current call, must be called right after acquiring a
+    /// unique reference.
+    #[inline(never)]
+    pub(crate) fn record_backtrace(&self) {
+        let backtrace = Backtrace::force_capture();
+        let mut registry = registry().lock().unwrap_or_else(|err| err.into_inner());
+        registry.insert(self.backtrace_key(), backtrace);
+    }
+
+    /// Removes the recorded backtrace, must be called while still holding the unique
+    /// reference, so it can't remove the backtrace of the next one.
+    #[inline]
+    pub(crate) fn clear_backtrace(&self) {
+        let mut registry = registry().lock().unwrap_or_else(|err| err.into_inner());
+        registry.remove(&self.backtrace_key());
+    }
+
+    /// Returns a backtrace of where the unique reference currently held was acquired.
+    ///
+    /// Only unique references acquired through [`borrow_mut`](Self::borrow_mut), or
+    /// methods built on it, and [`checked_borrow_mut`](Self::checked_borrow_mut) are
+    /// recorded. Returns `None` if `self` isn't uniquely borrowed.
+    pub fn acquire_backtrace(&self) -> Option<String> {
+        let registry = registry().lock().unwrap_or_else(|err| err.into_inner());
+        let backtrace = registry.get(&self.backtrace_key())?;
+        Some(backtrace.to_string())
+    }
+}
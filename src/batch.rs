@@ -0,0 +1,106 @@
+use std::mem;
+
+use crate::{AtomicBorrow, SharedGuard};
+
+impl AtomicBorrow {
+    /// Releases `n` shared references at once.
+    #[inline]
+    pub(crate) fn release_n(&self, n: usize) {
+        let prev = self.borrow.fetch_sub(n, Self::RELEASE);
+        debug_assert!(
+            prev & Self::SHARED_MASK >= n,
+            "borrow counter underflow, this means you released more times than you borrowed"
+        );
+
+        self.released(prev - n);
+    }
+}
+
+impl<'a, T> SharedGuard<'a, T> {
+    /// Combines `guards` sharing the same [`AtomicBorrow`] into a [`GuardBatch`], which
+    /// releases all of them with a single atomic operation.
+    ///
+    /// Returns `guards` if they don't all share the same borrow, or if it's empty.
+    #[inline]
+    pub fn into_batch(guards: Vec<Self>) -> Result<GuardBatch<'a, T>, Vec<Self>> {
+        let borrow = match guards.first() {
+            Some(guard) => guard.borrow,
+            None => return Err(guards),
+        };
+
+        if !guards
+            .iter()
+            .all(|guard| std::ptr::eq(guard.borrow, borrow))
+        {
+            return Err(guards);
+        }
+
+        let data = guards.into_iter().map(SharedGuard::forget).collect();
+        Ok(GuardBatch { data, borrow })
+    }
+}
+
+/// A batch of shared guards of the same [`AtomicBorrow`], released together when dropped.
+///
+/// Created by [`SharedGuard::into_batch`].
+pub struct GuardBatch<'a, T> {
+    data: Vec<*const T>,
+    borrow: &'a AtomicBorrow,
+}
+
+impl<'a, T> GuardBatch<'a, T> {
+    /// Gets the inner [`AtomicBorrow`].
+    ///
+    /// # Safety
+    /// The borrow guards the data, which may belong to an
+    /// [`AtomicRefCell`](crate::AtomicRefCell), so references must not be released
+    /// through it unless they were also acquired through it.
+    #[inline]
+    pub unsafe fn get_borrow(&self) -> &'a AtomicBorrow {
+        self.borrow
+    }
+
+    /// Returns the number of guards in the batch.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns true if the batch is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Gets the data of the guard at `index`.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.data.get(index).map(|&data| unsafe { &*data })
+    }
+
+    /// Iterates over the data of all guards.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.data.iter().map(|&data| unsafe { &*data })
+    }
+
+    /// Splits the batch back into individual guards.
+    #[inline]
+    pub fn into_guards(mut self) -> Vec<SharedGuard<'a, T>> {
+        let borrow = self.borrow;
+        let data = mem::take(&mut self.data);
+
+        data.into_iter()
+            .map(|data| SharedGuard { data, borrow })
+            .collect()
+    }
+}
+
+impl<'a, T> Drop for GuardBatch<'a, T> {
+    #[inline]
+    fn drop(&mut self) {
+        if !self.data.is_empty() {
+            self.borrow.release_n(self.data.len());
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use std::cell::RefCell;
+
+use crate::AtomicBorrow;
+
+thread_local! {
+    /// Shared references held by the current thread through the cache, keyed by the
+    /// address of their [`AtomicBorrow`].
+    static CACHE: RefCell<Vec<(usize, usize)>> = const { RefCell::new(Vec::new()) };
+}
+
+impl AtomicBorrow {
+    #[inline]
+    fn cache_key(&self) -> usize {
+        self as *const Self as usize
+    }
+
+    /// Tries to acquire a shared reference, coalescing with shared references already
+    /// held by the current thread.
+    ///
+    /// Only the first cached borrow on each thread touches the atomic counter, nested
+    /// borrows on the same thread are counted thread locally. References acquired
+    /// with this must be released with [`AtomicBorrow::cached_release`].
+    ///
+    /// Returns `true` if the reference was acquired.
+    #[inline]
+    pub fn cached_borrow(&self) -> bool {
+        let key = self.cache_key();
+
+        CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+
+            if let Some((_, count)) = cache.iter_mut().find(|(k, _)| *k == key) {
+                *count += 1;
+                return true;
+            }
+
+            if self.borrow() {
+                cache.push((key, 1));
+                true
+            } else {
+                false
+            }
+        })
+    }
+
+    /// Releases a shared reference acquired with [`AtomicBorrow::cached_borrow`].
+    ///
+    /// The atomic counter is only released by the last cached reference on the thread.
+    ///
+    /// # Panics
+    /// * If the current thread holds no cached reference to `self`.
+    #[inline]
+    pub fn cached_release(&self) {
+        let key = self.cache_key();
+
+        let last = CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+
+            let index = cache
+                .iter()
+                .position(|(k, _)| *k == key)
+                .expect("cached release without a cached borrow on this thread");
+
+            cache[index].1 -= 1;
+
+            if cache[index].1 == 0 {
+                cache.swap_remove(index);
+                true
+            } else {
+                false
+            }
+        });
+
+        if last {
+            self.release();
+        }
+    }
+}
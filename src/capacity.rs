@@ -0,0 +1,63 @@
+use std::sync::atomic::Ordering;
+
+use crate::AtomicBorrow;
+
+struct Capacity<const MAX: usize>;
+
+impl<const MAX: usize> Capacity<MAX> {
+    const ASSERT: () = assert!(
+        MAX <= AtomicBorrow::SHARED_MASK,
+        "capacity exceeds the shared borrow counter"
+    );
+}
+
+impl AtomicBorrow {
+    /// Statically asserts that `MAX` shared references fit in the counter.
+    ///
+    /// Fails to compile if `MAX` is greater than [`AtomicBorrow::SHARED_MASK`].
+    ///
+    /// ```compile_fail
+    /// # use atomic_borrow::AtomicBorrow;
+    /// const _: () = AtomicBorrow::assert_capacity::<{ usize::MAX }>();
+    /// ```
+    #[inline]
+    pub const fn assert_capacity<const MAX: usize>() {
+        #[allow(clippy::let_unit_value)]
+        let () = Capacity::<MAX>::ASSERT;
+    }
+
+    /// Tries to acquire a shared reference, failing if `MAX` shared references are
+    /// already held.
+    ///
+    /// Returns `true` if the reference was acquired.
+    #[inline]
+    pub fn borrow_n<const MAX: usize>(&self) -> bool {
+        Self::assert_capacity::<MAX>();
+
+        #[cfg(feature = "test-failpoints")]
+        if self.failpoint() {
+            return false;
+        }
+
+        let mut borrow = self.borrow.load(Ordering::Relaxed);
+
+        loop {
+            if borrow & Self::UNIQUE_MASK != 0 || borrow >= MAX {
+                return false;
+            }
+
+            match self.borrow.compare_exchange_weak(
+                borrow,
+                borrow + 1,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => {
+                    self.acquired();
+                    return true;
+                }
+                Err(actual) => borrow = actual,
+            }
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use std::{cell::UnsafeCell, mem};
+
+use crate::{AtomicBorrow, BorrowError, BorrowMutError, BorrowState, SharedGuard, UniqueGuard};
+
+/// A thread-safe mutable memory location with dynamically checked borrow rules.
+///
+/// The value lives in an [`UnsafeCell`] next to its [`AtomicBorrow`], and guards are only
+/// handed out once the borrow has been acquired, so the references they create never
+/// alias a unique one. This is the safe way to use [`SharedGuard`] and [`UniqueGuard`]
+/// when the data doesn't have to be stored apart from its borrow.
+///
+/// Getting the [`AtomicBorrow`] out of the cell or any of its guards is `unsafe`, so
+/// safe code can't release a borrow while its guard is still alive.
+#[derive(Debug, Default)]
+pub struct AtomicRefCell<T> {
+    borrow: AtomicBorrow,
+    value: UnsafeCell<T>,
+}
+
+unsafe impl<T: Send> Send for AtomicRefCell<T> {}
+#[cfg(not(feature = "single-threaded"))]
+unsafe impl<T: Send + Sync> Sync for AtomicRefCell<T> {}
+
+impl<T> AtomicRefCell<T> {
+    const_fn! {
+        /// Creates a new `AtomicRefCell` containing `value`.
+        #[inline]
+        pub fn new(value: T) -> Self {
+            Self {
+                borrow: AtomicBorrow::new(),
+                value: UnsafeCell::new(value),
+            }
+        }
+    }
+
+    /// Gets the inner [`AtomicBorrow`].
+    ///
+    /// # Safety
+    /// The borrow guards the value, so references must not be released through it
+    /// unless they were also acquired through it.
+    #[inline]
+    pub unsafe fn get_borrow(&self) -> &AtomicBorrow {
+        &self.borrow
+    }
+
+    /// Returns the current borrow state of the value.
+    #[inline]
+    pub fn state(&self) -> BorrowState {
+        self.borrow.state()
+    }
+
+    /// Gets a raw pointer to the inner value.
+    #[inline]
+    pub fn as_ptr(&self) -> *mut T {
+        self.value.get()
+    }
+
+    /// Immutably borrows the value.
+    ///
+    /// # Panics
+    /// Panics if the value is uniquely borrowed.
+    #[inline]
+    #[track_caller]
+    pub fn borrow(&self) -> SharedGuard<'_, T> {
+        match self.try_borrow() {
+            Ok(guard) => guard,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Mutably borrows the value.
+    ///
+    /// # Panics
+    /// Panics if the value is borrowed.
+    #[inline]
+    #[track_caller]
+    pub fn borrow_mut(&self) -> UniqueGuard<'_, T> {
+        match self.try_borrow_mut() {
+            Ok(guard) => guard,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Tries to immutably borrow the value.
+    #[inline]
+    pub fn try_borrow(&self) -> Result<SharedGuard<'_, T>, BorrowError> {
+        self.borrow.checked_borrow()?;
+
+        Ok(SharedGuard {
+            data: self.value.get(),
+            borrow: &self.borrow,
+        })
+    }
+
+    /// Tries to mutably borrow the value.
+    #[inline]
+    pub fn try_borrow_mut(&self) -> Result<UniqueGuard<'_, T>, BorrowMutError> {
+        self.borrow.checked_borrow_mut()?;
+
+        Ok(UniqueGuard {
+            data: self.value.get(),
+            borrow: &self.borrow,
+        })
+    }
+
+    /// Replaces the value with `value`, returning the old value.
+    ///
+    /// # Panics
+    /// Panics if the value is borrowed.
+    #[inline]
+    #[track_caller]
+    pub fn replace(&self, value: T) -> T {
+        mem::replace(&mut *self.borrow_mut(), value)
+    }
+
+    /// Replaces the value with one computed by `f`, returning the old value.
+    ///
+    /// # Panics
+    /// Panics if the value is borrowed.
+    #[inline]
+    #[track_caller]
+    pub fn replace_with(&self, f: impl FnOnce(&mut T) -> T) -> T {
+        let mut guard = self.borrow_mut();
+        let value = f(&mut guard);
+        mem::replace(&mut *guard, value)
+    }
+
+    /// Swaps the value with the value of `other`.
+    ///
+    /// # Panics
+    /// Panics if either value is borrowed, or if `self` and `other` are the same cell.
+    #[inline]
+    #[track_caller]
+    pub fn swap(&self, other: &Self) {
+        mem::swap(&mut *self.borrow_mut(), &mut *other.borrow_mut())
+    }
+
+    /// Returns a mutable reference to the value, no borrow checks are needed.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    /// Consumes `self`, returning the value.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<T: Default> AtomicRefCell<T> {
+    /// Takes the value, leaving `Default::default()` in its place.
+    ///
+    /// # Panics
+    /// Panics if the value is borrowed.
+    #[inline]
+    #[track_caller]
+    pub fn take(&self) -> T {
+        self.replace(T::default())
+    }
+}
+
+impl<T> From<T> for AtomicRefCell<T> {
+    #[inline]
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for AtomicRefCell<T> {
+    /// Serializes the value under a shared borrow.
+    ///
+    /// Fails if the value is uniquely borrowed.
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.try_borrow() {
+            Ok(guard) => guard.serialize(serializer),
+            Err(err) => Err(serde::ser::Error::custom(err)),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for AtomicRefCell<T> {
+    /// Deserializes the value into an unborrowed cell.
+    #[inline]
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Self::new)
+    }
+}
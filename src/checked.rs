@@ -0,0 +1,209 @@
+use core::{fmt, sync::atomic::Ordering};
+
+use crate::AtomicBorrow;
+
+/// An error returned by the checked [`AtomicBorrow`] methods.
+///
+/// Errors never allocate, and only depend on `core`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BorrowError {
+    /// The shared borrow counter would overflow.
+    Overflow,
+    /// `self` is uniquely borrowed.
+    UniquelyBorrowed,
+    /// `self` is shared borrowed.
+    SharedBorrowed,
+    /// `self` is not borrowed.
+    NotBorrowed,
+    /// `self` is not uniquely borrowed.
+    NotUniquelyBorrowed,
+}
+
+impl BorrowError {
+    /// Returns a static description of the error.
+    #[inline]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Overflow => "borrow counter overflowed",
+            Self::UniquelyBorrowed => "already uniquely borrowed",
+            Self::SharedBorrowed => "already shared borrowed",
+            Self::NotBorrowed => "not borrowed",
+            Self::NotUniquelyBorrowed => "not uniquely borrowed",
+        }
+    }
+}
+
+impl fmt::Display for BorrowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl core::error::Error for BorrowError {}
+
+/// An error returned when a unique reference can't be acquired.
+///
+/// Errors never allocate, and only depend on `core`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BorrowMutError {
+    /// `self` is uniquely borrowed.
+    UniquelyBorrowed,
+    /// `self` is shared borrowed.
+    SharedBorrowed,
+}
+
+impl BorrowMutError {
+    /// Returns a static description of the error.
+    #[inline]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::UniquelyBorrowed => "already uniquely borrowed",
+            Self::SharedBorrowed => "already shared borrowed",
+        }
+    }
+}
+
+impl fmt::Display for BorrowMutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl core::error::Error for BorrowMutError {}
+
+impl From<BorrowMutError> for BorrowError {
+    #[inline]
+    fn from(error: BorrowMutError) -> Self {
+        match error {
+            BorrowMutError::UniquelyBorrowed => Self::UniquelyBorrowed,
+            BorrowMutError::SharedBorrowed => Self::SharedBorrowed,
+        }
+    }
+}
+
+impl AtomicBorrow {
+    /// Tries to acquire a shared reference, never panicking.
+    ///
+    /// Unlike [`AtomicBorrow::borrow`], a failed acquire never writes to the counter.
+    #[inline]
+    pub fn checked_borrow(&self) -> Result<(), BorrowError> {
+        #[cfg(feature = "test-failpoints")]
+        if self.failpoint() {
+            return Err(BorrowError::UniquelyBorrowed);
+        }
+
+        let mut borrow = self.borrow.load(Ordering::Relaxed);
+
+        loop {
+            if borrow & Self::UNIQUE_MASK != 0 {
+                return Err(BorrowError::UniquelyBorrowed);
+            }
+
+            if borrow == Self::SHARED_MASK {
+                return Err(BorrowError::Overflow);
+            }
+
+            match self.counter_compare_exchange(borrow, borrow + 1, Ordering::Acquire) {
+                Ok(_) => {
+                    self.acquired();
+                    return Ok(());
+                }
+                Err(actual) => borrow = actual,
+            }
+        }
+    }
+
+    /// Tries to acquire a unique reference, never panicking.
+    #[inline]
+    pub fn checked_borrow_mut(&self) -> Result<(), BorrowMutError> {
+        #[cfg(feature = "test-failpoints")]
+        if self.failpoint() {
+            return Err(BorrowMutError::UniquelyBorrowed);
+        }
+
+        match self.counter_compare_exchange(0, Self::UNIQUE_MASK, Ordering::Acquire) {
+            Ok(_) => {
+                #[cfg(feature = "debug-backtrace")]
+                self.record_backtrace();
+
+                #[cfg(feature = "debug-owner")]
+                self.record_owner();
+
+                self.acquired();
+                Ok(())
+            }
+            Err(actual) if actual & Self::UNIQUE_MASK != 0 => Err(BorrowMutError::UniquelyBorrowed),
+            Err(_) => Err(BorrowMutError::SharedBorrowed),
+        }
+    }
+
+    /// Releases a shared reference, never panicking.
+    ///
+    /// The counter is left untouched if `self` isn't shared borrowed.
+    #[inline]
+    pub fn checked_release(&self) -> Result<(), BorrowError> {
+        let mut borrow = self.borrow.load(Ordering::Relaxed);
+
+        loop {
+            if borrow & Self::UNIQUE_MASK != 0 {
+                return Err(BorrowError::UniquelyBorrowed);
+            }
+
+            if borrow == 0 {
+                return Err(BorrowError::NotBorrowed);
+            }
+
+            match self.borrow.compare_exchange_weak(
+                borrow,
+                borrow - 1,
+                Self::RELEASE,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(actual) => borrow = actual,
+            }
+        }
+
+        self.released(borrow - 1);
+
+        Ok(())
+    }
+
+    /// Releases a unique reference, never panicking.
+    ///
+    /// The counter is left untouched if `self` isn't uniquely borrowed.
+    #[inline]
+    pub fn checked_release_mut(&self) -> Result<(), BorrowError> {
+        let mut borrow = self.borrow.load(Ordering::Relaxed);
+
+        #[cfg(feature = "debug-backtrace")]
+        if borrow & Self::UNIQUE_MASK != 0 {
+            self.clear_backtrace();
+        }
+
+        #[cfg(feature = "debug-owner")]
+        if borrow & Self::UNIQUE_MASK != 0 {
+            self.clear_owner();
+        }
+
+        loop {
+            if borrow & Self::UNIQUE_MASK == 0 {
+                return Err(BorrowError::NotUniquelyBorrowed);
+            }
+
+            match self.borrow.compare_exchange_weak(
+                borrow,
+                borrow & !Self::UNIQUE_MASK,
+                Self::RELEASE,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(actual) => borrow = actual,
+            }
+        }
+
+        self.released(borrow & !Self::UNIQUE_MASK);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,158 @@
+use std::{
+    cell::UnsafeCell,
+    hash::Hash,
+    ops::{Deref, DerefMut},
+};
+#[cfg(debug_assertions)]
+use std::{
+    hash::{DefaultHasher, Hasher},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::{AtomicBorrow, BorrowState, SharedGuard, UniqueGuard};
+
+/// An [`AtomicRefCell`](crate::AtomicRefCell) that detects the value being mutated
+/// without a unique borrow, eg. through a raw pointer, in debug builds.
+///
+/// When a unique borrow is released a checksum of the value is stored, and every shared
+/// borrow panics if the value no longer matches it. In release builds no checksums are
+/// computed.
+#[derive(Debug)]
+pub struct DebugCheckedCell<T: Hash> {
+    borrow: AtomicBorrow,
+    // only written under a unique borrow, so `borrow` orders it
+    #[cfg(debug_assertions)]
+    checksum: AtomicU64,
+    value: UnsafeCell<T>,
+}
+
+unsafe impl<T: Hash + Send> Send for DebugCheckedCell<T> {}
+#[cfg(not(feature = "single-threaded"))]
+unsafe impl<T: Hash + Send + Sync> Sync for DebugCheckedCell<T> {}
+
+impl<T: Hash> DebugCheckedCell<T> {
+    /// Creates a new `DebugCheckedCell` containing `value`.
+    #[inline]
+    pub fn new(value: T) -> Self {
+        Self {
+            borrow: AtomicBorrow::new(),
+            #[cfg(debug_assertions)]
+            checksum: AtomicU64::new(checksum(&value)),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Gets the inner [`AtomicBorrow`].
+    ///
+    /// # Safety
+    /// The borrow guards the value, so references must not be released through it
+    /// unless they were also acquired through it.
+    #[inline]
+    pub unsafe fn get_borrow(&self) -> &AtomicBorrow {
+        &self.borrow
+    }
+
+    /// Returns the current borrow state of the value.
+    #[inline]
+    pub fn state(&self) -> BorrowState {
+        self.borrow.state()
+    }
+
+    /// Gets a raw pointer to the inner value.
+    #[inline]
+    pub fn as_ptr(&self) -> *mut T {
+        self.value.get()
+    }
+
+    /// Tries to immutably borrow the value.
+    ///
+    /// # Panics
+    /// In debug builds, if the value was mutated since the last unique borrow was
+    /// released.
+    #[inline]
+    #[track_caller]
+    pub fn try_borrow(&self) -> Option<SharedGuard<'_, T>> {
+        if !self.borrow.borrow() {
+            return None;
+        }
+
+        let guard = SharedGuard {
+            data: self.value.get(),
+            borrow: &self.borrow,
+        };
+
+        #[cfg(debug_assertions)]
+        if checksum(&*guard) != self.checksum.load(Ordering::Relaxed) {
+            panic!("value was mutated without a unique borrow");
+        }
+
+        Some(guard)
+    }
+
+    /// Tries to mutably borrow the value.
+    #[inline]
+    pub fn try_borrow_mut(&self) -> Option<CheckedUniqueGuard<'_, T>> {
+        if !self.borrow.borrow_mut() {
+            return None;
+        }
+
+        Some(CheckedUniqueGuard {
+            guard: UniqueGuard {
+                data: self.value.get(),
+                borrow: &self.borrow,
+            },
+            #[cfg(debug_assertions)]
+            checksum: &self.checksum,
+        })
+    }
+
+    /// Consumes `self`, returning the value.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+#[cfg(debug_assertions)]
+#[inline]
+fn checksum<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A unique borrow of a [`DebugCheckedCell`], storing a checksum of the value when
+/// dropped.
+///
+/// Created by [`DebugCheckedCell::try_borrow_mut`].
+pub struct CheckedUniqueGuard<'a, T: Hash> {
+    guard: UniqueGuard<'a, T>,
+    #[cfg(debug_assertions)]
+    checksum: &'a AtomicU64,
+}
+
+impl<'a, T: Hash> Deref for CheckedUniqueGuard<'a, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<'a, T: Hash> DerefMut for CheckedUniqueGuard<'a, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
+
+impl<'a, T: Hash> Drop for CheckedUniqueGuard<'a, T> {
+    #[inline]
+    fn drop(&mut self) {
+        // still under the unique borrow, the guard releases it afterwards
+        #[cfg(debug_assertions)]
+        self.checksum
+            .store(checksum(&*self.guard), Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,50 @@
+use std::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+};
+
+use crate::{SharedGuard, UniqueGuard};
+
+macro_rules! impl_cmp {
+    ($guard:ident) => {
+        impl<'a, T: PartialEq> PartialEq<T> for $guard<'a, T> {
+            #[inline]
+            fn eq(&self, other: &T) -> bool {
+                **self == *other
+            }
+        }
+
+        impl<'a, 'b, T: PartialEq> PartialEq<$guard<'b, T>> for $guard<'a, T> {
+            #[inline]
+            fn eq(&self, other: &$guard<'b, T>) -> bool {
+                **self == **other
+            }
+        }
+
+        impl<'a, T: Eq> Eq for $guard<'a, T> {}
+
+        impl<'a, T: PartialOrd> PartialOrd<T> for $guard<'a, T> {
+            #[inline]
+            fn partial_cmp(&self, other: &T) -> Option<Ordering> {
+                (**self).partial_cmp(other)
+            }
+        }
+
+        impl<'a, 'b, T: PartialOrd> PartialOrd<$guard<'b, T>> for $guard<'a, T> {
+            #[inline]
+            fn partial_cmp(&self, other: &$guard<'b, T>) -> Option<Ordering> {
+                (**self).partial_cmp(&**other)
+            }
+        }
+
+        impl<'a, T: Hash> Hash for $guard<'a, T> {
+            #[inline]
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                (**self).hash(state)
+            }
+        }
+    };
+}
+
+impl_cmp!(SharedGuard);
+impl_cmp!(UniqueGuard);
@@ -0,0 +1,189 @@
+use std::{
+    cell::UnsafeCell,
+    marker::PhantomData,
+    mem,
+    ops::{Deref, DerefMut},
+    ptr::NonNull,
+};
+
+use crate::{AtomicBorrow, BorrowState};
+
+/// A value stored together with its [`AtomicBorrow`], at a fixed offset.
+///
+/// Since the borrow can be found from the address of the value, the guards only need to
+/// store a single pointer, see [`CompactGuard`] and [`CompactGuardMut`].
+#[derive(Debug, Default)]
+#[repr(C)]
+pub struct Colocated<T> {
+    borrow: AtomicBorrow,
+    value: UnsafeCell<T>,
+}
+
+unsafe impl<T: Send> Send for Colocated<T> {}
+#[cfg(not(feature = "single-threaded"))]
+unsafe impl<T: Send + Sync> Sync for Colocated<T> {}
+
+impl<T> Colocated<T> {
+    const VALUE_OFFSET: usize = mem::offset_of!(Self, value);
+
+    const_fn! {
+        /// Creates a new `Colocated` containing `value`.
+        #[inline]
+        pub fn new(value: T) -> Self {
+            Self {
+                borrow: AtomicBorrow::new(),
+                value: UnsafeCell::new(value),
+            }
+        }
+    }
+
+    /// Gets the inner [`AtomicBorrow`].
+    ///
+    /// # Safety
+    /// The borrow guards the value, so references must not be released through it
+    /// unless they were also acquired through it.
+    #[inline]
+    pub unsafe fn get_borrow(&self) -> &AtomicBorrow {
+        &self.borrow
+    }
+
+    /// Returns the current borrow state of the value.
+    #[inline]
+    pub fn state(&self) -> BorrowState {
+        self.borrow.state()
+    }
+
+    /// Tries to immutably borrow the value.
+    #[inline]
+    pub fn try_borrow(&self) -> Option<CompactGuard<'_, T>> {
+        if !self.borrow.borrow() {
+            return None;
+        }
+
+        Some(CompactGuard {
+            data: self.value_ptr(),
+            marker: PhantomData,
+        })
+    }
+
+    /// Tries to mutably borrow the value.
+    #[inline]
+    pub fn try_borrow_mut(&self) -> Option<CompactGuardMut<'_, T>> {
+        if !self.borrow.borrow_mut() {
+            return None;
+        }
+
+        Some(CompactGuardMut {
+            data: self.value_ptr(),
+            marker: PhantomData,
+        })
+    }
+
+    /// Consumes `self`, returning the value.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+
+    #[inline]
+    fn value_ptr(&self) -> NonNull<T> {
+        // derived from all of `self`, so the guards may step back to the borrow
+        let ptr = NonNull::from(self).cast::<u8>();
+        unsafe { ptr.add(Self::VALUE_OFFSET).cast() }
+    }
+
+    /// Finds the borrow of the value at `data`.
+    ///
+    /// # Safety
+    /// `data` must point to the value of a `Colocated<T>` that outlives `'a`.
+    #[inline]
+    unsafe fn borrow_of<'a>(data: NonNull<T>) -> &'a AtomicBorrow {
+        // SAFETY: `Colocated` is `repr(C)`, so the value is always `VALUE_OFFSET` bytes
+        // after the start, where the borrow is
+        unsafe {
+            let colocated = data.as_ptr().byte_sub(Self::VALUE_OFFSET) as *const Self;
+            &(*colocated).borrow
+        }
+    }
+}
+
+/// A guard that releases a shared reference when dropped, storing only the data pointer.
+///
+/// Created by [`Colocated::try_borrow`].
+#[repr(transparent)]
+pub struct CompactGuard<'a, T> {
+    data: NonNull<T>,
+    marker: PhantomData<&'a Colocated<T>>,
+}
+
+impl<'a, T> CompactGuard<'a, T> {
+    /// Gets the inner [`AtomicBorrow`].
+    ///
+    /// # Safety
+    /// The borrow guards the value, so references must not be released through it
+    /// unless they were also acquired through it.
+    #[inline]
+    pub unsafe fn get_borrow(&self) -> &'a AtomicBorrow {
+        unsafe { Colocated::borrow_of(self.data) }
+    }
+}
+
+impl<'a, T> Deref for CompactGuard<'a, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        unsafe { self.data.as_ref() }
+    }
+}
+
+impl<'a, T> Drop for CompactGuard<'a, T> {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe { Colocated::borrow_of(self.data) }.release();
+    }
+}
+
+/// A guard that releases a unique reference when dropped, storing only the data pointer.
+///
+/// Created by [`Colocated::try_borrow_mut`].
+#[repr(transparent)]
+pub struct CompactGuardMut<'a, T> {
+    data: NonNull<T>,
+    marker: PhantomData<&'a Colocated<T>>,
+}
+
+impl<'a, T> CompactGuardMut<'a, T> {
+    /// Gets the inner [`AtomicBorrow`].
+    ///
+    /// # Safety
+    /// The borrow guards the value, so references must not be released through it
+    /// unless they were also acquired through it.
+    #[inline]
+    pub unsafe fn get_borrow(&self) -> &'a AtomicBorrow {
+        unsafe { Colocated::borrow_of(self.data) }
+    }
+}
+
+impl<'a, T> Deref for CompactGuardMut<'a, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        unsafe { self.data.as_ref() }
+    }
+}
+
+impl<'a, T> DerefMut for CompactGuardMut<'a, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { self.data.as_mut() }
+    }
+}
+
+impl<'a, T> Drop for CompactGuardMut<'a, T> {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe { Colocated::borrow_of(self.data) }.release_mut();
+    }
+}
@@ -0,0 +1,123 @@
+use std::{
+    fmt,
+    ops::Deref,
+    sync::Mutex,
+    thread::{self, ThreadId},
+    time::{Duration, Instant},
+};
+
+use crate::AtomicBorrow;
+
+#[derive(Default)]
+struct ConvoyState {
+    owner: Option<ThreadId>,
+    released: Option<Instant>,
+    handoffs: usize,
+}
+
+/// An [`AtomicBorrow`] that detects lock convoys.
+///
+/// A convoy is when unique references are repeatedly handed off between different
+/// threads in quick succession. When `handoffs` consecutive handoffs each happen
+/// within `window` of the previous release, the callback is invoked with the number
+/// of handoffs.
+pub struct ConvoyBorrow {
+    borrow: AtomicBorrow,
+    state: Mutex<ConvoyState>,
+    handoffs: usize,
+    window: Duration,
+    callback: Box<dyn Fn(usize) + Send + Sync>,
+}
+
+impl ConvoyBorrow {
+    /// Creates a new `ConvoyBorrow`.
+    #[inline]
+    pub fn new(
+        handoffs: usize,
+        window: Duration,
+        callback: impl Fn(usize) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            borrow: AtomicBorrow::new(),
+            state: Mutex::default(),
+            handoffs,
+            window,
+            callback: Box::new(callback),
+        }
+    }
+
+    /// Tries to acquire a unique reference, recording handoffs.
+    ///
+    /// Returns `true` if the reference was acquired.
+    #[inline]
+    pub fn borrow_mut(&self) -> bool {
+        if !self.borrow.borrow_mut() {
+            return false;
+        }
+
+        self.acquired();
+        true
+    }
+
+    /// Spins until a unique reference can be acquired, recording handoffs.
+    #[inline]
+    pub fn spin_borrow_mut(&self) {
+        self.borrow.spin_borrow_mut();
+        self.acquired();
+    }
+
+    /// Releases a unique reference, recording the release time.
+    #[inline]
+    pub fn release_mut(&self) {
+        self.state.lock().unwrap().released = Some(Instant::now());
+        self.borrow.release_mut();
+    }
+
+    #[cold]
+    fn acquired(&self) {
+        let now = Instant::now();
+        let current = thread::current().id();
+
+        let mut state = self.state.lock().unwrap();
+
+        let quick = match state.released {
+            Some(released) => now.duration_since(released) <= self.window,
+            None => false,
+        };
+
+        if quick && state.owner.is_some_and(|owner| owner != current) {
+            state.handoffs += 1;
+        } else {
+            state.handoffs = 0;
+        }
+
+        state.owner = Some(current);
+
+        if state.handoffs >= self.handoffs {
+            let handoffs = state.handoffs;
+            state.handoffs = 0;
+            drop(state);
+
+            (self.callback)(handoffs);
+        }
+    }
+}
+
+impl Deref for ConvoyBorrow {
+    type Target = AtomicBorrow;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.borrow
+    }
+}
+
+impl fmt::Debug for ConvoyBorrow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConvoyBorrow")
+            .field("borrow", &self.borrow)
+            .field("handoffs", &self.handoffs)
+            .field("window", &self.window)
+            .finish_non_exhaustive()
+    }
+}
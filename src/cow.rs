@@ -0,0 +1,55 @@
+use std::sync::{Arc, Mutex};
+
+use crate::{AtomicRefCell, OwnedSharedGuard, OwnedUniqueGuard};
+
+/// A copy-on-write value.
+///
+/// Writers mutate the current version in place when there are no readers, otherwise
+/// they clone it into a new version, leaving readers of the old version undisturbed.
+#[derive(Debug, Default)]
+pub struct CowBorrow<T> {
+    current: Mutex<Arc<AtomicRefCell<T>>>,
+}
+
+impl<T> CowBorrow<T> {
+    /// Creates a new `CowBorrow` containing `value`.
+    #[inline]
+    pub fn new(value: T) -> Self {
+        Self {
+            current: Mutex::new(Arc::new(AtomicRefCell::new(value))),
+        }
+    }
+
+    /// Tries to borrow the current version.
+    ///
+    /// Fails if the current version is being written.
+    #[inline]
+    pub fn read(&self) -> Option<OwnedSharedGuard<T>> {
+        let current = self.current.lock().unwrap().clone();
+        OwnedSharedGuard::try_new(current)
+    }
+}
+
+impl<T: Clone> CowBorrow<T> {
+    /// Uniquely borrows the current version if there are no readers, otherwise clones
+    /// it into a new current version and borrows that.
+    ///
+    /// Fails if the current version is already being written.
+    #[inline]
+    pub fn make_mut(&self) -> Option<OwnedUniqueGuard<T>> {
+        let mut current = self.current.lock().unwrap();
+
+        if let Some(guard) = OwnedUniqueGuard::try_new(current.clone()) {
+            return Some(guard);
+        }
+
+        let old = OwnedSharedGuard::try_new(current.clone())?;
+        let new = Arc::new(AtomicRefCell::new(T::clone(&old)));
+
+        // `new` isn't shared yet, so this can't fail
+        let guard = OwnedUniqueGuard::try_new(new.clone())?;
+        *current = new;
+
+        Some(guard)
+    }
+}
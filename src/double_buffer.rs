@@ -0,0 +1,100 @@
+use std::{
+    hint,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{AtomicRefCell, BorrowError, SharedGuard, UniqueGuard};
+
+/// A double buffer, where readers read the front buffer while a writer writes the
+/// back buffer, then swaps them.
+///
+/// Each buffer is protected by its own [`AtomicBorrow`](crate::AtomicBorrow), so a
+/// swap never tears a buffer readers are still holding, instead writing to it fails
+/// until they have released it. Swapping takes the write guard of the back buffer, so
+/// a buffer is only published once the writer is done with it.
+#[derive(Debug, Default)]
+pub struct AtomicDoubleBuffer<T> {
+    buffers: [AtomicRefCell<T>; 2],
+    front: AtomicUsize,
+}
+
+impl<T> AtomicDoubleBuffer<T> {
+    /// Creates a new `AtomicDoubleBuffer` with `front` and `back` buffers.
+    #[inline]
+    pub fn new(front: T, back: T) -> Self {
+        Self {
+            buffers: [AtomicRefCell::new(front), AtomicRefCell::new(back)],
+            front: AtomicUsize::new(0),
+        }
+    }
+
+    /// Tries to borrow the front buffer.
+    ///
+    /// Only fails if the shared borrow counter of the front buffer would overflow.
+    #[inline]
+    pub fn read(&self) -> Option<SharedGuard<'_, T>> {
+        loop {
+            let front = self.front.load(Ordering::Acquire);
+
+            match self.buffers[front].try_borrow() {
+                // retry if we raced with a swap
+                Ok(guard) if self.front.load(Ordering::Acquire) == front => return Some(guard),
+                Ok(_) => {}
+                // a swap published the buffer and is about to release its write guard
+                Err(BorrowError::UniquelyBorrowed) => hint::spin_loop(),
+                Err(_) => return None,
+            }
+        }
+    }
+
+    /// Tries to uniquely borrow the back buffer.
+    ///
+    /// Fails if readers still hold the back buffer from before the last swap, or if
+    /// it's already being written.
+    #[inline]
+    pub fn write(&self) -> Option<UniqueGuard<'_, T>> {
+        loop {
+            let front = self.front.load(Ordering::Acquire);
+            let guard = self.buffers[front ^ 1].try_borrow_mut().ok()?;
+
+            // retry if a swap made the buffer the front one before we got it
+            if self.front.load(Ordering::Acquire) == front {
+                return Some(guard);
+            }
+        }
+    }
+
+    /// Swaps the front and back buffers, publishing the back buffer written through
+    /// `guard` to readers.
+    ///
+    /// The buffers are swapped before `guard` is released, so no other writer can get to
+    /// the back buffer in between.
+    ///
+    /// # Panics
+    /// If `guard` doesn't borrow the back buffer of `self`.
+    #[inline]
+    pub fn swap(&self, guard: UniqueGuard<'_, T>) {
+        let back = self.front.load(Ordering::Relaxed) ^ 1;
+        assert!(
+            std::ptr::eq(guard.ptr(), self.buffers[back].as_ptr()),
+            "guard doesn't borrow the back buffer"
+        );
+
+        // the guard keeps other swaps out, so this can't race with another flip
+        self.front.store(back, Ordering::Release);
+        drop(guard);
+    }
+
+    /// Consumes `self`, returning the front and back buffers.
+    #[inline]
+    pub fn into_inner(self) -> (T, T) {
+        let front = self.front.into_inner();
+        let [a, b] = self.buffers.map(AtomicRefCell::into_inner);
+
+        if front == 0 {
+            (a, b)
+        } else {
+            (b, a)
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use std::sync::atomic::Ordering;
+
+use crate::{AtomicBorrow, SharedGuard, UniqueGuard};
+
+impl AtomicBorrow {
+    /// Turns the unique reference held by the caller into a shared reference, in a
+    /// single atomic operation, so no other unique reference can be acquired in between.
+    ///
+    /// # Panics
+    /// If `self` is not uniquely borrowed. Only with `debug_assertions` enabled.
+    #[inline]
+    pub fn downgrade(&self) {
+        #[cfg(feature = "debug-backtrace")]
+        self.clear_backtrace();
+
+        #[cfg(feature = "debug-owner")]
+        self.clear_owner();
+
+        // clears the unique bit and adds a shared reference, keeping the increments of
+        // readers that are about to fail
+        let prev = self
+            .borrow
+            .fetch_sub(Self::UNIQUE_MASK - 1, Ordering::Release);
+        debug_assert_ne!(prev & Self::UNIQUE_MASK, 0, "downgrade of shared borrow");
+
+        self.released(prev - (Self::UNIQUE_MASK - 1));
+    }
+
+    /// Uniquely borrows `data` to run `write`, then downgrades to a shared borrow to run
+    /// `read`, without any other writer getting a chance in between.
+    ///
+    /// Returns `None` if the unique reference couldn't be acquired. The borrow is
+    /// released even if `write` or `read` panics.
+    ///
+    /// # Safety
+    /// * Any borrows of `data` must be registered with `self`.
+    /// * `data` must be a valid pointer until this returns.
+    #[inline]
+    pub unsafe fn write_then_read<T, R>(
+        &self,
+        data: *mut T,
+        write: impl FnOnce(&mut T),
+        read: impl FnOnce(&T) -> R,
+    ) -> Option<R> {
+        // SAFETY: upheld by the caller
+        let mut guard = unsafe { UniqueGuard::try_new(data, self)? };
+        write(&mut guard);
+
+        let guard = UniqueGuard::downgrade(guard);
+        Some(read(&guard))
+    }
+}
+
+impl<'a, T> UniqueGuard<'a, T> {
+    /// Turns the guard into a [`SharedGuard`], see [`AtomicBorrow::downgrade`].
+    ///
+    /// This is an associated function to avoid conflicting with methods on `T`.
+    #[inline]
+    pub fn downgrade(s: Self) -> SharedGuard<'a, T> {
+        let borrow = s.borrow;
+        let data = s.forget();
+        borrow.downgrade();
+
+        SharedGuard { data, borrow }
+    }
+}
@@ -0,0 +1,50 @@
+use std::iter::FusedIterator;
+
+use crate::UniqueGuard;
+
+impl<'a, T> UniqueGuard<'a, Vec<T>> {
+    /// Drains the vector by value, holding the unique reference until the iterator is
+    /// dropped.
+    ///
+    /// Like [`Vec::drain`], the vector is left empty even if the iterator isn't exhausted.
+    #[inline]
+    pub fn into_drain(mut self) -> GuardDrain<'a, T> {
+        // popping from the back keeps draining `O(n)`
+        self.reverse();
+        GuardDrain { guard: self }
+    }
+}
+
+/// An iterator draining a uniquely borrowed [`Vec`] by value.
+///
+/// Created by [`UniqueGuard::into_drain`].
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct GuardDrain<'a, T> {
+    guard: UniqueGuard<'a, Vec<T>>,
+}
+
+impl<'a, T> Iterator for GuardDrain<'a, T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.guard.pop()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.guard.len(), Some(self.guard.len()))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for GuardDrain<'a, T> {}
+
+impl<'a, T> FusedIterator for GuardDrain<'a, T> {}
+
+impl<'a, T> Drop for GuardDrain<'a, T> {
+    #[inline]
+    fn drop(&mut self) {
+        // drop the remaining elements before the guard releases the borrow
+        self.guard.clear();
+    }
+}
@@ -0,0 +1,144 @@
+use std::sync::atomic::Ordering;
+#[cfg(feature = "startup-elision")]
+use std::{sync::atomic::AtomicBool, thread};
+
+use crate::AtomicBorrow;
+
+/// Whether the program has promised that only one thread exists.
+#[cfg(feature = "startup-elision")]
+static SINGLE_THREADED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(feature = "startup-elision")]
+impl AtomicBorrow {
+    /// Makes [`borrow`](Self::borrow), [`borrow_mut`](Self::borrow_mut),
+    /// [`release`](Self::release) and [`release_mut`](Self::release_mut) on every
+    /// `AtomicBorrow` use a plain load and store instead of a read-modify-write, until
+    /// [`end_single_threaded`](Self::end_single_threaded) is called.
+    ///
+    /// Meant for programs that set up shared state on the main thread before spawning
+    /// any workers. Spawn the first thread with [`spawn`](Self::spawn), or call
+    /// [`end_single_threaded`](Self::end_single_threaded) before spawning it, to turn
+    /// the fast path off again.
+    ///
+    /// # Safety
+    /// From the call until [`end_single_threaded`](Self::end_single_threaded) returns,
+    /// the calling thread must be the only thread touching any `AtomicBorrow`. This
+    /// includes threads spawned before the call, threads created by other libraries,
+    /// and signal handlers. Otherwise two threads may both acquire a unique reference.
+    #[inline]
+    pub unsafe fn assume_single_threaded() {
+        SINGLE_THREADED.store(true, Ordering::Relaxed);
+    }
+
+    /// Turns off the fast path enabled by
+    /// [`assume_single_threaded`](Self::assume_single_threaded).
+    ///
+    /// Threads spawned afterwards see every write made before the call, since spawning
+    /// synchronizes with the spawned thread.
+    #[inline]
+    pub fn end_single_threaded() {
+        SINGLE_THREADED.store(false, Ordering::Relaxed);
+    }
+
+    /// Returns `true` while borrows skip the atomic read-modify-write, see
+    /// [`assume_single_threaded`](Self::assume_single_threaded).
+    #[inline]
+    pub fn is_single_threaded() -> bool {
+        SINGLE_THREADED.load(Ordering::Relaxed)
+    }
+
+    /// Spawns a thread like [`std::thread::spawn`], calling
+    /// [`end_single_threaded`](Self::end_single_threaded) first.
+    #[inline]
+    pub fn spawn<F, T>(f: F) -> thread::JoinHandle<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        Self::end_single_threaded();
+        thread::spawn(f)
+    }
+}
+
+impl AtomicBorrow {
+    /// Like `fetch_add`, but a plain load and store while single threaded.
+    #[inline(always)]
+    pub(crate) fn counter_add(&self, val: usize, order: Ordering) -> usize {
+        #[cfg(feature = "startup-elision")]
+        if Self::is_single_threaded() {
+            let prev = self.borrow.load(Ordering::Relaxed);
+            self.borrow.store(prev.wrapping_add(val), Ordering::Relaxed);
+            return prev;
+        }
+
+        self.borrow.fetch_add(val, order)
+    }
+
+    /// Like `fetch_sub`, but a plain load and store while single threaded.
+    #[inline(always)]
+    pub(crate) fn counter_sub(&self, val: usize, order: Ordering) -> usize {
+        #[cfg(feature = "startup-elision")]
+        if Self::is_single_threaded() {
+            let prev = self.borrow.load(Ordering::Relaxed);
+            self.borrow.store(prev.wrapping_sub(val), Ordering::Relaxed);
+            return prev;
+        }
+
+        self.borrow.fetch_sub(val, order)
+    }
+
+    /// Like `fetch_and`, but a plain load and store while single threaded.
+    #[inline(always)]
+    pub(crate) fn counter_and(&self, val: usize, order: Ordering) -> usize {
+        #[cfg(feature = "startup-elision")]
+        if Self::is_single_threaded() {
+            let prev = self.borrow.load(Ordering::Relaxed);
+            self.borrow.store(prev & val, Ordering::Relaxed);
+            return prev;
+        }
+
+        self.borrow.fetch_and(val, order)
+    }
+
+    /// Like `compare_exchange`, but a plain load and store while single threaded.
+    #[inline(always)]
+    pub(crate) fn counter_compare_exchange(
+        &self,
+        current: usize,
+        new: usize,
+        order: Ordering,
+    ) -> Result<usize, usize> {
+        #[cfg(feature = "startup-elision")]
+        if Self::is_single_threaded() {
+            let actual = self.borrow.load(Ordering::Relaxed);
+            if actual != current {
+                return Err(actual);
+            }
+
+            self.borrow.store(new, Ordering::Relaxed);
+            return Ok(actual);
+        }
+
+        self.borrow
+            .compare_exchange(current, new, order, Ordering::Relaxed)
+    }
+
+    /// Swaps 0 for `new`, like `compare_exchange`, but a plain load and store while
+    /// single threaded.
+    #[inline(always)]
+    pub(crate) fn counter_acquire_from_zero(&self, new: usize, order: Ordering) -> bool {
+        #[cfg(feature = "startup-elision")]
+        if Self::is_single_threaded() {
+            if self.borrow.load(Ordering::Relaxed) != 0 {
+                return false;
+            }
+
+            self.borrow.store(new, Ordering::Relaxed);
+            return true;
+        }
+
+        self.borrow
+            .compare_exchange(0, new, order, Ordering::Relaxed)
+            .is_ok()
+    }
+}
@@ -0,0 +1,93 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex, OnceLock,
+    },
+};
+
+use crate::AtomicBorrow;
+
+/// The number of armed failpoints, used to skip the registry when empty.
+static FAILPOINTS: AtomicUsize = AtomicUsize::new(0);
+
+/// The number of calls left until an [`AtomicBorrow`] fails, keyed by its address.
+fn registry() -> &'static Mutex<HashMap<usize, usize>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<usize, usize>>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+impl AtomicBorrow {
+    #[inline]
+    fn failpoint_key(&self) -> usize {
+        self as *const Self as usize
+    }
+
+    /// Makes the next call to [`borrow`](Self::borrow) or
+    /// [`borrow_mut`](Self::borrow_mut) on `self` fail, as if there was contention.
+    ///
+    /// The checked variants count too, and fail with `UniquelyBorrowed`, so this also
+    /// makes [`AtomicRefCell::try_borrow`](crate::AtomicRefCell::try_borrow) fail.
+    #[inline]
+    pub fn fail_next_borrow(&self) {
+        self.fail_nth_borrow(1);
+    }
+
+    /// Makes the `n`th next call to [`borrow`](Self::borrow) or
+    /// [`borrow_mut`](Self::borrow_mut) on `self` fail, as if there was contention.
+    ///
+    /// Counts the same calls as [`fail_next_borrow`](Self::fail_next_borrow).
+    ///
+    /// Replaces any failure armed earlier on `self`, and disarms it if `n` is 0. Failures
+    /// are keyed by address, so disarm them before dropping `self`.
+    pub fn fail_nth_borrow(&self, n: usize) {
+        let mut registry = registry().lock().unwrap();
+
+        let armed = match n {
+            0 => registry.remove(&self.failpoint_key()),
+            n => registry.insert(self.failpoint_key(), n),
+        };
+
+        match (armed.is_some(), n > 0) {
+            (false, true) => FAILPOINTS.fetch_add(1, Ordering::Relaxed),
+            (true, false) => FAILPOINTS.fetch_sub(1, Ordering::Relaxed),
+            _ => 0,
+        };
+    }
+
+    /// Sets the number of shared references, replacing the whole state of `self`.
+    ///
+    /// Meant for testing the overflow boundary, without acquiring billions of references.
+    ///
+    /// # Safety
+    /// Any guards of `self` alive now must be forgotten, and `count` must not exceed
+    /// [`SHARED_MASK`](Self::SHARED_MASK).
+    pub unsafe fn force_shared_count(&self, count: usize) {
+        assert!(count <= Self::SHARED_MASK, "count exceeds `SHARED_MASK`");
+        self.borrow.store(count, Ordering::Release);
+    }
+
+    /// Counts a borrow attempt, returning true if it should fail.
+    #[inline]
+    pub(crate) fn failpoint(&self) -> bool {
+        if FAILPOINTS.load(Ordering::Relaxed) == 0 {
+            return false;
+        }
+
+        let mut registry = registry().lock().unwrap();
+
+        let Some(left) = registry.get_mut(&self.failpoint_key()) else {
+            return false;
+        };
+
+        *left -= 1;
+
+        if *left == 0 {
+            registry.remove(&self.failpoint_key());
+            FAILPOINTS.fetch_sub(1, Ordering::Relaxed);
+            true
+        } else {
+            false
+        }
+    }
+}
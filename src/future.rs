@@ -0,0 +1,206 @@
+//! Futures woken when an [`AtomicBorrow`] is released.
+//!
+//! `AtomicBorrow` has to stay exactly one `usize`, so it can be shared with C through
+//! [`AtomicBorrow::from_c_ptr`], and there is no room for a waker list on the borrow
+//! itself. Wakers are kept in a global registry instead, split into the same buckets as
+//! the waiter counts, so only borrows hashing into the same bucket share a lock, and a
+//! release without waiters in its bucket never takes one.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{self, AtomicUsize, Ordering},
+        Mutex,
+    },
+    task::{Context, Poll, Waker},
+};
+
+use crate::{
+    waiters::{self, WaiterCounts, BUCKETS},
+    AtomicBorrow,
+};
+
+/// The wakers registered on each borrow, so releases can skip the registry.
+static WAITERS: WaiterCounts = WaiterCounts::new();
+
+/// Wakers waiting on the [`AtomicBorrow`]s of a bucket, with the address of the borrow
+/// and the id of the future that registered them.
+type Registry = Mutex<Vec<(usize, usize, Waker)>>;
+
+fn registry(borrow: &AtomicBorrow) -> &'static Registry {
+    static REGISTRY: [Registry; BUCKETS] = [const { Mutex::new(Vec::new()) }; BUCKETS];
+    &REGISTRY[waiters::bucket_of(borrow)]
+}
+
+impl AtomicBorrow {
+    #[inline]
+    fn waker_key(&self) -> usize {
+        self as *const Self as usize
+    }
+
+    /// Wakes all wakers waiting on `self`.
+    #[inline]
+    pub(crate) fn wake_waiters(&self) {
+        // the release was `SeqCst`, see `AtomicBorrow::RELEASE`
+        if !WAITERS.may_have_waiters(self) {
+            return;
+        }
+
+        let key = self.waker_key();
+        let mut wakers = Vec::new();
+
+        {
+            let mut registry = registry(self).lock().unwrap();
+            let mut index = 0;
+
+            while index < registry.len() {
+                if registry[index].0 == key {
+                    wakers.push(registry.swap_remove(index).2);
+                } else {
+                    index += 1;
+                }
+            }
+        }
+
+        for waker in wakers {
+            WAITERS.remove(self);
+            waker.wake();
+        }
+    }
+
+    /// Returns a future resolving once a unique reference has been acquired.
+    ///
+    /// The future is woken when `self` is released, rather than polling repeatedly.
+    #[inline]
+    pub fn borrow_mut_async(&self) -> BorrowMutFuture<'_> {
+        BorrowMutFuture {
+            waiter: ReleaseWaiter::new(self),
+        }
+    }
+
+    /// Returns a future resolving once `self` is fully released.
+    ///
+    /// Unlike [`Self::borrow_mut_async`] this doesn't acquire anything, so the borrow
+    /// may be taken again by the time the future is polled to completion. Useful for
+    /// waiting for readers and writers to drain before tearing something down.
+    #[inline]
+    pub fn when_free(&self) -> WhenFree<'_> {
+        WhenFree {
+            waiter: ReleaseWaiter::new(self),
+        }
+    }
+}
+
+/// The waker registration of a future waiting for a release.
+#[derive(Debug)]
+struct ReleaseWaiter<'a> {
+    borrow: &'a AtomicBorrow,
+    // assigned on the first registration, futures may move between polls
+    id: Option<usize>,
+}
+
+impl<'a> ReleaseWaiter<'a> {
+    #[inline]
+    fn new(borrow: &'a AtomicBorrow) -> Self {
+        Self { borrow, id: None }
+    }
+
+    /// Polls `ready`, registering the waker of `cx` to be woken the next time the
+    /// borrow is released if it returns `false`.
+    #[inline]
+    fn poll(&mut self, cx: &mut Context<'_>, ready: impl Fn(&AtomicBorrow) -> bool) -> Poll<()> {
+        if ready(self.borrow) {
+            self.deregister();
+            return Poll::Ready(());
+        }
+
+        self.register(cx.waker());
+
+        // check again in case the borrow was released before the waker was registered
+        atomic::fence(Ordering::SeqCst);
+
+        if ready(self.borrow) {
+            self.deregister();
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+
+    fn register(&mut self, waker: &Waker) {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+        let id = *self
+            .id
+            .get_or_insert_with(|| NEXT_ID.fetch_add(1, Ordering::Relaxed));
+
+        let key = self.borrow.waker_key();
+        let mut wakers = registry(self.borrow).lock().unwrap();
+
+        match wakers.iter_mut().find(|(k, i, _)| *k == key && *i == id) {
+            Some((_, _, w)) => w.clone_from(waker),
+            None => {
+                wakers.push((key, id, waker.clone()));
+                WAITERS.add(self.borrow);
+            }
+        }
+    }
+
+    /// Removes the waker, if it wasn't woken already.
+    fn deregister(&mut self) {
+        let Some(id) = self.id else {
+            return;
+        };
+
+        let key = self.borrow.waker_key();
+        let mut wakers = registry(self.borrow).lock().unwrap();
+
+        if let Some(index) = wakers.iter().position(|(k, i, _)| *k == key && *i == id) {
+            wakers.swap_remove(index);
+            WAITERS.remove(self.borrow);
+        }
+    }
+}
+
+impl<'a> Drop for ReleaseWaiter<'a> {
+    #[inline]
+    fn drop(&mut self) {
+        self.deregister();
+    }
+}
+
+/// A future resolving once a unique reference has been acquired.
+///
+/// Created by [`AtomicBorrow::borrow_mut_async`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct BorrowMutFuture<'a> {
+    waiter: ReleaseWaiter<'a>,
+}
+
+impl<'a> Future for BorrowMutFuture<'a> {
+    type Output = ();
+
+    #[inline]
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.waiter.poll(cx, AtomicBorrow::borrow_mut)
+    }
+}
+
+/// A future resolving once an [`AtomicBorrow`] is fully released.
+///
+/// Created by [`AtomicBorrow::when_free`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct WhenFree<'a> {
+    waiter: ReleaseWaiter<'a>,
+}
+
+impl<'a> Future for WhenFree<'a> {
+    type Output = ();
+
+    #[inline]
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.waiter.poll(cx, |borrow| !borrow.is_borrowed())
+    }
+}
@@ -0,0 +1,88 @@
+use std::ops::{Deref, DerefMut};
+
+use crate::AtomicBorrow;
+
+impl AtomicBorrow {
+    /// Tries to uniquely borrow `data`, a field guarded by `child`, inside a structure
+    /// guarded by `parent`.
+    ///
+    /// `parent` is shared borrowed first, then `child` is uniquely borrowed. The
+    /// [`HierarchicalGuard`] releases them in the opposite order. Always acquiring from
+    /// the top of the hierarchy down means a thread holding a child never waits for its
+    /// parent, so the levels can't deadlock each other.
+    ///
+    /// If `child` can't be borrowed, `parent` is released again and `None` is returned.
+    ///
+    /// # Safety
+    /// * Any borrows of `data` must be registered with `child`.
+    /// * `data` must be a valid pointer for the entire lifetime `'a`.
+    #[inline]
+    pub unsafe fn borrow_child_mut<'a, T>(
+        parent: &'a AtomicBorrow,
+        child: &'a AtomicBorrow,
+        data: *mut T,
+    ) -> Option<HierarchicalGuard<'a, T>> {
+        if !parent.borrow() {
+            return None;
+        }
+
+        if !child.borrow_mut() {
+            parent.release();
+            return None;
+        }
+
+        Some(HierarchicalGuard {
+            data,
+            parent,
+            child,
+        })
+    }
+}
+
+/// A unique borrow of a field, held together with a shared borrow of its parent.
+///
+/// Created by [`AtomicBorrow::borrow_child_mut`].
+pub struct HierarchicalGuard<'a, T> {
+    data: *mut T,
+    parent: &'a AtomicBorrow,
+    child: &'a AtomicBorrow,
+}
+
+impl<'a, T> HierarchicalGuard<'a, T> {
+    /// Gets the parent [`AtomicBorrow`], which is shared borrowed.
+    #[inline]
+    pub fn get_parent(&self) -> &'a AtomicBorrow {
+        self.parent
+    }
+
+    /// Gets the child [`AtomicBorrow`], which is uniquely borrowed.
+    #[inline]
+    pub fn get_child(&self) -> &'a AtomicBorrow {
+        self.child
+    }
+}
+
+impl<'a, T> Deref for HierarchicalGuard<'a, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.data }
+    }
+}
+
+impl<'a, T> DerefMut for HierarchicalGuard<'a, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.data }
+    }
+}
+
+impl<'a, T> Drop for HierarchicalGuard<'a, T> {
+    #[inline]
+    fn drop(&mut self) {
+        // release bottom up, the reverse of acquiring
+        self.child.release_mut();
+        self.parent.release();
+    }
+}
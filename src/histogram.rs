@@ -0,0 +1,32 @@
+use std::sync::Mutex;
+
+use hdrhistogram::Histogram;
+
+use crate::AtomicBorrow;
+
+impl AtomicBorrow {
+    /// Spins until a unique reference can be acquired, like
+    /// [`spin_borrow_mut`](Self::spin_borrow_mut), then records the number of failed
+    /// attempts into `hist`.
+    ///
+    /// Recording into the same histogram from many borrows gives a distribution of how
+    /// contended they are.
+    #[inline]
+    pub fn record_into(&self, hist: &Mutex<Histogram<u64>>) {
+        let mut attempts = 0u64;
+
+        while !self.borrow_mut() {
+            if attempts < Self::SPIN_COUNT as u64 {
+                std::hint::spin_loop();
+            } else {
+                std::thread::yield_now();
+            }
+
+            attempts += 1;
+        }
+
+        // a poisoned histogram is still fine to record into
+        let mut hist = hist.lock().unwrap_or_else(|err| err.into_inner());
+        hist.saturating_record(attempts);
+    }
+}
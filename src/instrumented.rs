@@ -0,0 +1,130 @@
+use std::{fmt, ops::Deref};
+
+use crate::AtomicBorrow;
+
+/// Receives the events of an [`InstrumentedBorrow`], eg. to forward them to a metrics
+/// backend like Prometheus or StatsD.
+///
+/// Every method defaults to doing nothing, so recorders only implement the events they
+/// care about. They're called on the borrowing thread right after the operation, so
+/// they should be cheap.
+pub trait BorrowRecorder: Sync {
+    /// A shared reference was acquired.
+    fn borrowed(&self) {}
+
+    /// A unique reference was acquired.
+    fn borrowed_mut(&self) {}
+
+    /// A shared reference was released.
+    fn released(&self) {}
+
+    /// A unique reference was released.
+    fn released_mut(&self) {}
+
+    /// Acquiring a reference failed because of a conflicting borrow. `unique` tells
+    /// whether a unique reference was requested.
+    fn contended(&self, unique: bool) {
+        let _ = unique;
+    }
+}
+
+/// An [`AtomicBorrow`] reporting every operation to a [`BorrowRecorder`].
+///
+/// The recorder is `'static`, so an `InstrumentedBorrow` can be created in a `static`,
+/// like a `#[global_allocator]`. Only operations through `InstrumentedBorrow` itself
+/// are reported, not those through the inner [`AtomicBorrow`], and plain
+/// [`AtomicBorrow`]s pay nothing for this.
+pub struct InstrumentedBorrow {
+    borrow: AtomicBorrow,
+    recorder: &'static dyn BorrowRecorder,
+}
+
+impl InstrumentedBorrow {
+    const_fn! {
+        /// Creates a new `InstrumentedBorrow` reporting to `recorder`.
+        #[inline]
+        pub fn new(recorder: &'static dyn BorrowRecorder) -> Self {
+            Self {
+                borrow: AtomicBorrow::new(),
+                recorder,
+            }
+        }
+    }
+
+    /// Gets the recorder events are reported to.
+    #[inline]
+    pub fn recorder(&self) -> &'static dyn BorrowRecorder {
+        self.recorder
+    }
+
+    /// Tries to acquire a shared reference, reporting the outcome.
+    ///
+    /// Returns `true` if the reference was acquired.
+    #[inline]
+    #[track_caller]
+    pub fn borrow(&self) -> bool {
+        let acquired = self.borrow.borrow();
+
+        if acquired {
+            self.recorder.borrowed();
+        } else {
+            self.recorder.contended(false);
+        }
+
+        acquired
+    }
+
+    /// Tries to acquire a unique reference, reporting the outcome.
+    ///
+    /// Returns `true` if the reference was acquired.
+    #[inline]
+    pub fn borrow_mut(&self) -> bool {
+        let acquired = self.borrow.borrow_mut();
+
+        if acquired {
+            self.recorder.borrowed_mut();
+        } else {
+            self.recorder.contended(true);
+        }
+
+        acquired
+    }
+
+    /// Releases a shared reference, reporting it.
+    ///
+    /// # Panics
+    /// Same as [`AtomicBorrow::release`].
+    #[inline]
+    pub fn release(&self) {
+        self.borrow.release();
+        self.recorder.released();
+    }
+
+    /// Releases a unique reference, reporting it.
+    ///
+    /// # Panics
+    /// Same as [`AtomicBorrow::release_mut`].
+    #[inline]
+    pub fn release_mut(&self) {
+        self.borrow.release_mut();
+        self.recorder.released_mut();
+    }
+}
+
+impl Deref for InstrumentedBorrow {
+    type Target = AtomicBorrow;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.borrow
+    }
+}
+
+impl fmt::Debug for InstrumentedBorrow {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InstrumentedBorrow")
+            .field("borrow", &self.borrow)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,58 @@
+use std::cell::Cell;
+
+use crate::AtomicBorrow;
+
+thread_local! {
+    static SEED: Cell<u32> = const { Cell::new(0) };
+}
+
+/// Returns a cheap pseudo random number from a thread local xorshift generator.
+#[inline]
+pub(crate) fn xorshift() -> u32 {
+    SEED.with(|seed| {
+        let mut x = seed.get();
+
+        if x == 0 {
+            // seed with the address of the thread local, which differs per thread
+            x = (seed as *const Cell<u32> as usize as u32) | 1;
+        }
+
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+
+        seed.set(x);
+        x
+    })
+}
+
+impl AtomicBorrow {
+    const JITTER_MAX_SHIFT: u32 = 8;
+
+    /// Spins until a unique reference can be acquired, waiting a random and
+    /// exponentially growing amount of time between attempts.
+    ///
+    /// Staggering the retries avoids every waiting thread retrying at once when the
+    /// reference is released.
+    #[inline]
+    pub fn spin_borrow_mut_jittered(&self) {
+        let mut shift = 0;
+
+        for _ in 0..Self::SPIN_COUNT {
+            if self.borrow_mut() {
+                return;
+            }
+
+            let spins = xorshift() & ((1 << shift) - 1);
+            for _ in 0..=spins {
+                std::hint::spin_loop();
+            }
+
+            shift = (shift + 1).min(Self::JITTER_MAX_SHIFT);
+        }
+
+        while !self.borrow_mut() {
+            std::thread::yield_now();
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    RwLock,
+};
+
+use crate::AtomicBorrow;
+
+type LeakHook = Box<dyn Fn(&AtomicBorrow) + Send + Sync>;
+
+/// Whether a hook is set, used to skip the lock when not.
+static HOOKED: AtomicBool = AtomicBool::new(false);
+
+static HOOK: RwLock<Option<LeakHook>> = RwLock::new(None);
+
+impl AtomicBorrow {
+    /// Sets a hook called whenever any [`AtomicBorrow`] is dropped while still borrowed,
+    /// replacing the previous hook.
+    ///
+    /// Meant for test harnesses asserting that no guards are leaked across a whole test
+    /// run. The hook is only called in debug builds.
+    pub fn set_leak_hook(hook: impl Fn(&AtomicBorrow) + Send + Sync + 'static) {
+        let mut current = HOOK.write().unwrap_or_else(|err| err.into_inner());
+        *current = Some(Box::new(hook));
+        HOOKED.store(true, Ordering::Release);
+    }
+
+    /// Removes the hook set by [`set_leak_hook`](Self::set_leak_hook).
+    pub fn clear_leak_hook() {
+        let mut current = HOOK.write().unwrap_or_else(|err| err.into_inner());
+        HOOKED.store(false, Ordering::Relaxed);
+        *current = None;
+    }
+
+    #[cold]
+    fn leaked(&self) {
+        let hook = HOOK.read().unwrap_or_else(|err| err.into_inner());
+
+        if let Some(hook) = &*hook {
+            hook(self);
+        }
+    }
+}
+
+impl Drop for AtomicBorrow {
+    #[inline]
+    fn drop(&mut self) {
+        if HOOKED.load(Ordering::Acquire) && self.borrow.load(Ordering::Relaxed) != 0 {
+            self.leaked();
+        }
+    }
+}
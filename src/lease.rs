@@ -0,0 +1,122 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::AtomicBorrow;
+
+#[derive(Debug, Default)]
+struct LeaseState {
+    id: u64,
+    expires: Option<Instant>,
+}
+
+/// An [`AtomicBorrow`] handing out unique references that expire.
+///
+/// Expired leases can be forcibly released with [`LeaseBorrow::reap_expired`], so a
+/// leaked [`LeaseGuard`] can't lock the resource forever. Since a lease can be
+/// revoked under its holder, [`LeaseGuard`] doesn't give access to any data, holders
+/// must check [`LeaseGuard::is_valid`] before touching the resource.
+#[derive(Debug, Default)]
+pub struct LeaseBorrow {
+    borrow: AtomicBorrow,
+    state: Mutex<LeaseState>,
+}
+
+impl LeaseBorrow {
+    const_fn! {
+        /// Creates a new `LeaseBorrow`.
+        #[inline]
+        pub fn new() -> Self {
+            Self {
+                borrow: AtomicBorrow::new(),
+                state: Mutex::new(LeaseState {
+                    id: 0,
+                    expires: None,
+                }),
+            }
+        }
+    }
+
+    /// Gets the inner [`AtomicBorrow`].
+    #[inline]
+    pub fn get_borrow(&self) -> &AtomicBorrow {
+        &self.borrow
+    }
+
+    /// Tries to acquire a unique reference, which expires after `ttl`.
+    #[inline]
+    pub fn lease_borrow_mut(&self, ttl: Duration) -> Option<LeaseGuard<'_>> {
+        if !self.borrow.borrow_mut() {
+            return None;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        state.id += 1;
+        state.expires = Some(Instant::now() + ttl);
+
+        Some(LeaseGuard {
+            lease: self,
+            id: state.id,
+        })
+    }
+
+    /// Releases the current lease if it has expired.
+    ///
+    /// Returns `true` if a lease was reaped.
+    #[inline]
+    pub fn reap_expired(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+
+        match state.expires {
+            Some(expires) if expires <= Instant::now() => {
+                state.expires = None;
+                self.borrow.release_mut();
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// A guard that releases a leased unique reference when dropped, unless the lease was
+/// already reaped.
+#[derive(Debug)]
+pub struct LeaseGuard<'a> {
+    lease: &'a LeaseBorrow,
+    id: u64,
+}
+
+impl<'a> LeaseGuard<'a> {
+    /// Returns true if the lease hasn't been reaped.
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        let state = self.lease.state.lock().unwrap();
+        state.id == self.id && state.expires.is_some()
+    }
+
+    /// Returns the time left until the lease expires, `None` if it has been reaped.
+    #[inline]
+    pub fn remaining(&self) -> Option<Duration> {
+        let state = self.lease.state.lock().unwrap();
+
+        match state.expires {
+            Some(expires) if state.id == self.id => {
+                Some(expires.saturating_duration_since(Instant::now()))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Drop for LeaseGuard<'a> {
+    #[inline]
+    fn drop(&mut self) {
+        let mut state = self.lease.state.lock().unwrap();
+
+        // the lease may have been reaped, and possibly handed out again
+        if state.id == self.id && state.expires.take().is_some() {
+            self.lease.borrow.release_mut();
+        }
+    }
+}
@@ -1,15 +1,223 @@
 #![deny(unsafe_op_in_unsafe_fn)]
+// Threaded WASM needs nightly for `-C target-feature=+atomics` and `-Z build-std` anyway.
+#![cfg_attr(
+    all(
+        feature = "wasm-atomics",
+        target_arch = "wasm32",
+        target_feature = "atomics"
+    ),
+    feature(stdarch_wasm_atomic_wait)
+)]
 
 //! An simple atomic reference counter.
 
+/// Defines a hot function, inlined according to the `no-inline` and `force-inline`
+/// features, where `no-inline` takes precedence.
+macro_rules! hot_fn {
+    ($(#[$attr:meta])* $vis:vis fn $($rest:tt)*) => {
+        $(#[$attr])*
+        #[cfg_attr(feature = "no-inline", inline(never))]
+        #[cfg_attr(all(feature = "force-inline", not(feature = "no-inline")), inline(always))]
+        #[cfg_attr(not(any(feature = "no-inline", feature = "force-inline")), inline)]
+        $vis fn $($rest)*
+    };
+}
+
+/// Defines a `const fn`, which isn't `const` under loom since its atomics can't be
+/// created in const contexts.
+macro_rules! const_fn {
+    ($(#[$attr:meta])* $vis:vis fn $($rest:tt)*) => {
+        $(#[$attr])*
+        #[cfg(not(loom))]
+        $vis const fn $($rest)*
+
+        $(#[$attr])*
+        #[cfg(loom)]
+        $vis fn $($rest)*
+    };
+}
+
+/// Asserts at compile time that the state of a borrow with up to `max_readers` shared
+/// references fits in `bits` bits, see [`AtomicBorrow::bits_required`].
+///
+/// # Example
+/// ```
+/// atomic_borrow::assert_borrow_bits!(1000, 11);
+/// ```
+///
+/// ```compile_fail
+/// atomic_borrow::assert_borrow_bits!(1024, 11);
+/// ```
+#[macro_export]
+macro_rules! assert_borrow_bits {
+    ($max_readers:expr, $bits:expr $(,)?) => {
+        const _: () = assert!(
+            $crate::AtomicBorrow::bits_required($max_readers) <= $bits,
+            "the borrow state doesn't fit in the bits",
+        );
+    };
+}
+
+mod adaptive;
+mod array;
+#[cfg(feature = "debug-backtrace")]
+mod backtrace;
+mod batch;
+mod cache;
+mod capacity;
+mod cell;
+mod checked;
+mod checksum;
+mod cmp;
+mod compact;
+#[cfg(feature = "diagnostics")]
+mod convoy;
+mod cow;
+mod double_buffer;
+mod downgrade;
+mod drain;
+mod elision;
+#[cfg(feature = "test-failpoints")]
+mod failpoint;
+#[cfg(feature = "async")]
+mod future;
+mod hierarchy;
+#[cfg(feature = "hdrhistogram")]
+mod histogram;
+mod instrumented;
+mod jitter;
+#[cfg(all(debug_assertions, not(loom)))]
+mod leak;
+mod lease;
+#[cfg(feature = "tracing")]
+mod logged;
+mod low_bit;
+mod mailbox;
+mod mapped;
+mod migrate;
+#[cfg(all(feature = "registry", not(feature = "single-threaded")))]
+mod named;
+mod nonblocking;
+#[cfg(feature = "futures")]
+mod notify;
+mod owned;
+#[cfg(feature = "debug-owner")]
+mod owner;
+#[cfg(all(feature = "rayon", not(feature = "single-threaded")))]
+mod par;
+#[cfg(feature = "parking")]
+mod park;
+mod peak;
+mod policy;
+mod pool;
+#[cfg(feature = "usdt")]
+mod probe;
+mod reborrow;
+mod reclaim;
+mod reentrant;
+mod relaxed;
+mod release;
+mod revocable;
+mod scope;
+mod seqcst;
+mod signal;
+mod sink;
+#[cfg(feature = "parking")]
+mod smart;
+mod state;
+#[cfg(feature = "futures")]
+mod stream;
+mod swmr;
+mod transaction;
+#[cfg(feature = "single-threaded")]
+mod unsync;
+#[cfg(any(feature = "parking", feature = "async"))]
+mod waiters;
+#[cfg(all(
+    feature = "wasm-atomics",
+    target_arch = "wasm32",
+    target_feature = "atomics",
+    not(feature = "single-threaded")
+))]
+mod wasm_wait;
+#[cfg(feature = "state-stream")]
+mod watch;
+
+pub use adaptive::AdaptiveBorrow;
+pub use array::{AtomicBorrowArray, PaddedAtomicBorrowArray};
+pub use batch::GuardBatch;
+pub use cell::AtomicRefCell;
+pub use checked::{BorrowError, BorrowMutError};
+pub use checksum::{CheckedUniqueGuard, DebugCheckedCell};
+pub use compact::{Colocated, CompactGuard, CompactGuardMut};
+#[cfg(feature = "diagnostics")]
+pub use convoy::ConvoyBorrow;
+pub use cow::CowBorrow;
+pub use double_buffer::AtomicDoubleBuffer;
+pub use drain::GuardDrain;
+#[cfg(feature = "async")]
+pub use future::{BorrowMutFuture, WhenFree};
+pub use hierarchy::HierarchicalGuard;
+pub use instrumented::{BorrowRecorder, InstrumentedBorrow};
+pub use lease::{LeaseBorrow, LeaseGuard};
+#[cfg(feature = "tracing")]
+pub use logged::LoggedGuard;
+pub use low_bit::LowBitBorrow;
+pub use mailbox::Mailbox;
+pub use mapped::{MappedSharedGuard, MappedUniqueGuard};
+#[cfg(feature = "futures")]
+pub use notify::NotifyBorrow;
+pub use owned::{AtomicRc, OwnedSharedGuard, OwnedUniqueGuard};
+#[cfg(all(feature = "rayon", not(feature = "single-threaded")))]
+pub use par::GuardedParIter;
+pub use peak::PeakBorrow;
+pub use policy::BorrowPolicy;
+pub use pool::{SlotPool, SlotRef};
+pub use reborrow::SharedRef;
+pub use reclaim::ReclaimBorrow;
+pub use reentrant::{ReentrantBorrow, ReentrantUniqueGuard};
+pub use relaxed::RelaxedBorrow;
+pub use release::ReleaseGuard;
+pub use revocable::RevocableBorrow;
+pub use scope::SendSharedGuard;
+pub use signal::SignalSafeBorrow;
+pub use sink::ReleaseSink;
+#[cfg(feature = "parking")]
+pub use smart::{SmartBorrow, WaitStrategy};
+pub use state::BorrowState;
+#[cfg(feature = "futures")]
+pub use stream::DrainStream;
+pub use swmr::SwmrBorrow;
+pub use transaction::Transaction;
+#[cfg(feature = "state-stream")]
+pub use watch::StateStream;
+
 use std::{
-    ops::{Deref, DerefMut},
-    sync::atomic::{AtomicUsize, Ordering},
+    fmt,
+    io::{self, Read, Write},
+    mem::MaybeUninit,
+    ops::{Deref, DerefMut, Index, IndexMut},
+    pin::Pin,
+    sync::atomic::Ordering,
 };
 
+#[cfg(loom)]
+use loom::sync::atomic::AtomicUsize;
+#[cfg(not(any(loom, feature = "single-threaded")))]
+use std::sync::atomic::AtomicUsize;
+#[cfg(all(not(loom), feature = "single-threaded"))]
+use unsync::AtomicUsize;
+
 /// An atomic reference counter.
+///
+/// `AtomicBorrow` has the same size and alignment as [`AtomicUsize`], and therefore
+/// `usize` on all targets with atomics, so it can be embedded in `#[repr(C)]` structs
+/// shared with C as a `size_t` manipulated atomically.
+///
+/// With the `single-threaded` feature, the counter is a plain `Cell<usize>` instead,
+/// which is faster but makes `AtomicBorrow`, and everything containing it, `!Sync`.
 #[repr(transparent)]
-#[derive(Debug, Default)]
+#[derive(Default)]
 pub struct AtomicBorrow {
     borrow: AtomicUsize,
 }
@@ -22,14 +230,47 @@ impl AtomicBorrow {
 
     const SPIN_COUNT: usize = 1 << 10;
 
-    /// Creates a new `AtomicBorrow`.
-    #[inline]
-    pub const fn new() -> Self {
-        Self {
-            borrow: AtomicUsize::new(0),
+    const_fn! {
+        /// Creates a new `AtomicBorrow`.
+        #[inline]
+        pub fn new() -> Self {
+            Self {
+                borrow: AtomicUsize::new(0),
+            }
         }
     }
 
+    /// Creates a reference to an `AtomicBorrow` from a pointer to a `usize`, e.g. one
+    /// provided by C.
+    ///
+    /// # Safety
+    /// * `ptr` must be aligned to `align_of::<AtomicBorrow>()`, which is the same as
+    ///   `align_of::<usize>()` on all targets with atomics.
+    /// * `ptr` must be valid for reads and writes for the entire lifetime `'a`.
+    /// * All accesses to `ptr` during `'a` must be atomic.
+    #[inline]
+    pub const unsafe fn from_c_ptr<'a>(ptr: *mut usize) -> &'a Self {
+        // SAFETY: `AtomicBorrow` is a transparent wrapper of `AtomicUsize`, which has
+        // the same layout as `usize`, as asserted below
+        unsafe { &*(ptr as *const Self) }
+    }
+
+    /// Returns the number of bits needed for the state of a borrow with up to
+    /// `max_readers` shared references, including the unique bit.
+    ///
+    /// Meant for sizing bitfields packing the state together with other data, usable in
+    /// const contexts and with [`assert_borrow_bits!`].
+    #[inline]
+    pub const fn bits_required(max_readers: usize) -> u32 {
+        usize::BITS - max_readers.leading_zeros() + 1
+    }
+
+    /// Returns a pointer to the underlying `usize`, e.g. to pass to C.
+    #[inline]
+    pub const fn as_ptr(&self) -> *mut usize {
+        self as *const Self as *mut usize
+    }
+
     /// Returns number of shared borrows.
     #[inline]
     pub fn shared_count(&self) -> usize {
@@ -48,44 +289,171 @@ impl AtomicBorrow {
         self.borrow.load(Ordering::Acquire) != 0
     }
 
-    /// Tries to acquire a shared reference.
+    /// Returns number of shared borrows, which never exceeds [`AtomicBorrow::SHARED_MASK`].
+    ///
+    /// This is the same as [`AtomicBorrow::shared_count`], since the count is masked.
+    #[inline]
+    pub fn shared_count_saturating(&self) -> usize {
+        self.shared_count()
+    }
+
+    /// Returns true if the shared count is at most one borrow away from overflowing.
     ///
-    /// Returns `true` if the reference was acquired.
+    /// Meant for monitoring, to warn before [`AtomicBorrow::borrow`] panics.
     #[inline]
-    pub fn borrow(&self) -> bool {
-        let prev = self.borrow.fetch_add(1, Ordering::Acquire);
+    pub fn is_at_capacity(&self) -> bool {
+        self.shared_count() >= Self::SHARED_MASK - 1
+    }
+
+    /// Returns a human readable summary of the borrow state, e.g.
+    /// `AtomicBorrow { shared: 3, unique: false }`.
+    pub fn debug_summary(&self) -> String {
+        format!("{:?}", self)
+    }
 
+    hot_fn! {
+        /// Tries to acquire a shared reference.
+        ///
+        /// Returns `true` if the reference was acquired.
+        ///
+        /// # Panics
+        /// If there are already [`SHARED_MASK`](Self::SHARED_MASK) shared references. The
+        /// counter is left as it was, so borrowing works again once some are released.
+        #[track_caller]
+        pub fn borrow(&self) -> bool {
+            #[cfg(feature = "test-failpoints")]
+            if self.failpoint() {
+                return false;
+            }
+
+            let prev = self.counter_add(1, Ordering::Acquire);
+
+            // both the unique bit and a full counter make `prev` at least `SHARED_MASK`,
+            // so the common case only needs a single comparison
+            if prev >= Self::SHARED_MASK {
+                self.borrow_failed(prev)
+            } else {
+                self.acquired();
+                true
+            }
+        }
+    }
+
+    #[cold]
+    #[inline(never)]
+    #[track_caller]
+    fn borrow_failed(&self, prev: usize) -> bool {
         if prev & Self::SHARED_MASK == Self::SHARED_MASK {
+            // undo the increment, which carried into the unique bit
+            self.counter_sub(1, Ordering::Relaxed);
             panic!("borrow counter overflowed");
         }
 
-        if prev & Self::UNIQUE_MASK != 0 {
-            // we're already uniquely borrowed, so undo the increment and return false
-            self.borrow.fetch_sub(1, Ordering::Release);
-            false
-        } else {
-            true
+        // we're already uniquely borrowed, so undo the increment and return false
+        if self.counter_sub(1, Self::RELEASE) == 1 {
+            // the unique reference was released while we held the increment, so its
+            // release didn't see `self` become free, wake the waiters in its place
+            self.released(0);
         }
+
+        false
     }
 
-    /// Tries to acquire a unique reference.
-    ///
-    /// Returns `true` if the reference was acquired.
-    #[inline]
-    pub fn borrow_mut(&self) -> bool {
-        self.borrow
-            .compare_exchange(0, Self::UNIQUE_MASK, Ordering::Acquire, Ordering::Relaxed)
-            .is_ok()
+    hot_fn! {
+        /// Tries to acquire a shared reference, like [`borrow`](Self::borrow), but only
+        /// writes to the counter if the reference can be acquired.
+        ///
+        /// [`borrow`](Self::borrow) always increments, and has to decrement again when
+        /// `self` is uniquely borrowed, so failing readers make the cache line bounce
+        /// between them and the writer twice. This loads first and uses a compare
+        /// exchange instead, so failing costs no writes, but it has to retry when other
+        /// readers change the count concurrently. Prefer it when readers often run into
+        /// a writer, and [`borrow`](Self::borrow) when readers mostly contend with each
+        /// other.
+        ///
+        /// Returns `true` if the reference was acquired.
+        ///
+        /// # Panics
+        /// If there are already [`SHARED_MASK`](Self::SHARED_MASK) shared references.
+        #[track_caller]
+        pub fn borrow_cas(&self) -> bool {
+            #[cfg(feature = "test-failpoints")]
+            if self.failpoint() {
+                return false;
+            }
+
+            let mut state = self.borrow.load(Ordering::Relaxed);
+
+            loop {
+                if state & Self::UNIQUE_MASK != 0 {
+                    return false;
+                }
+
+                if state == Self::SHARED_MASK {
+                    panic!("borrow counter overflowed");
+                }
+
+                match self.borrow.compare_exchange_weak(
+                    state,
+                    state + 1,
+                    Ordering::Acquire,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        self.acquired();
+                        return true;
+                    }
+                    Err(current) => state = current,
+                }
+            }
+        }
     }
 
-    /// Releases a shared reference.
-    ///
-    /// # Panics.
-    /// * If `self` is not borrowed. Only with `debug_assertions` enabled.
-    /// * If `self` is uniquely borrowed. Only with `debug_assertions` enabled.
+    hot_fn! {
+        /// Tries to acquire a unique reference.
+        ///
+        /// Returns `true` if the reference was acquired.
+        pub fn borrow_mut(&self) -> bool {
+            #[cfg(feature = "test-failpoints")]
+            if self.failpoint() {
+                return false;
+            }
+
+            let acquired = self.counter_acquire_from_zero(Self::UNIQUE_MASK, Ordering::Acquire);
+
+            if acquired {
+                #[cfg(feature = "debug-backtrace")]
+                self.record_backtrace();
+
+                #[cfg(feature = "debug-owner")]
+                self.record_owner();
+
+                self.acquired();
+            }
+
+            acquired
+        }
+    }
+
+    hot_fn! {
+        /// Releases a shared reference.
+        ///
+        /// Without `debug_assertions` and the `parking`, `async`, `state-stream`,
+        /// `wasm-atomics` and `startup-elision` features, this compiles to a single
+        /// branchless `fetch_sub`.
+        ///
+        /// # Panics.
+        /// * If `self` is not borrowed. Only with `debug_assertions` enabled.
+        /// * If `self` is uniquely borrowed. Only with `debug_assertions` enabled.
+        pub fn release(&self) {
+            self.release_prev();
+        }
+    }
+
+    /// Releases a shared reference, returning the previous state.
     #[inline]
-    pub fn release(&self) {
-        let prev = self.borrow.fetch_sub(1, Ordering::Release);
+    pub(crate) fn release_prev(&self) -> usize {
+        let prev = self.counter_sub(1, Self::RELEASE);
         debug_assert_ne!(
             prev, 0,
             "borrow counter underflow, this means you released more times than you borrowed"
@@ -95,20 +463,89 @@ impl AtomicBorrow {
             0,
             "shared release of unique borrow"
         );
+
+        self.released(prev - 1);
+        prev
     }
 
-    /// Releases a unique reference.
-    ///
-    /// # Panics.
-    /// * If `self` is not uniquely borrowed. Only with `debug_assertions` enabled.
+    hot_fn! {
+        /// Releases a unique reference.
+        ///
+        /// Without `debug_assertions` and the `parking`, `async`, `state-stream`,
+        /// `wasm-atomics`, `startup-elision`, `debug-backtrace` and `debug-owner` features,
+        /// this compiles to a single branchless `fetch_and`.
+        ///
+        /// # Panics.
+        /// * If `self` is not uniquely borrowed. Only with `debug_assertions` enabled.
+        pub fn release_mut(&self) {
+            self.release_mut_prev();
+        }
+    }
+
+    /// Releases a unique reference, returning the previous state.
     #[inline]
-    pub fn release_mut(&self) {
-        let prev = self.borrow.fetch_and(!Self::UNIQUE_MASK, Ordering::Release);
+    pub(crate) fn release_mut_prev(&self) -> usize {
+        #[cfg(feature = "debug-backtrace")]
+        self.clear_backtrace();
+
+        #[cfg(feature = "debug-owner")]
+        self.clear_owner();
+
+        let prev = self.counter_and(!Self::UNIQUE_MASK, Self::RELEASE);
         debug_assert_ne!(
             prev & Self::UNIQUE_MASK,
             0,
             "unique release of shared borrow"
         );
+
+        self.released(prev & !Self::UNIQUE_MASK);
+        prev
+    }
+
+    /// Called after every successful acquisition.
+    #[inline]
+    pub(crate) fn acquired(&self) {
+        #[cfg(feature = "state-stream")]
+        self.state_changed();
+    }
+
+    /// The ordering of every release that may free `self`.
+    ///
+    /// Blocking and async waiters count themselves before checking the state, and
+    /// releases check the count after changing it. Both sides need `SeqCst` to be sure
+    /// to see each other. On x86 this is the same instruction as `Release`.
+    pub(crate) const RELEASE: Ordering = if cfg!(any(feature = "parking", feature = "async")) {
+        Ordering::SeqCst
+    } else {
+        Ordering::Release
+    };
+
+    /// Called after every release with the new state.
+    #[inline]
+    #[allow(unused_variables)]
+    pub(crate) fn released(&self, state: usize) {
+        #[cfg(feature = "parking")]
+        if state == 0 {
+            self.unpark();
+        }
+
+        #[cfg(feature = "async")]
+        if state == 0 {
+            self.wake_waiters();
+        }
+
+        #[cfg(all(
+            feature = "wasm-atomics",
+            target_arch = "wasm32",
+            target_feature = "atomics",
+            not(feature = "single-threaded")
+        ))]
+        if state == 0 {
+            self.notify_waiters();
+        }
+
+        #[cfg(feature = "state-stream")]
+        self.state_changed();
     }
 
     /// Spins until a shared reference can be acquired.
@@ -122,6 +559,9 @@ impl AtomicBorrow {
             std::hint::spin_loop();
         }
 
+        #[cfg(feature = "usdt")]
+        self.probe_contended(false);
+
         while !self.borrow() {
             std::thread::yield_now();
         }
@@ -138,12 +578,43 @@ impl AtomicBorrow {
             std::hint::spin_loop();
         }
 
+        #[cfg(feature = "usdt")]
+        self.probe_contended(true);
+
         while !self.borrow_mut() {
             std::thread::yield_now();
         }
     }
 }
 
+#[cfg(not(loom))]
+const _: () = {
+    assert!(std::mem::size_of::<AtomicBorrow>() == std::mem::size_of::<usize>());
+    assert!(std::mem::align_of::<AtomicBorrow>() == std::mem::align_of::<usize>());
+};
+
+impl fmt::Debug for AtomicBorrow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let borrow = self.borrow.load(Ordering::Relaxed);
+
+        f.debug_struct("AtomicBorrow")
+            .field("shared", &(borrow & Self::SHARED_MASK))
+            .field("unique", &(borrow & Self::UNIQUE_MASK != 0))
+            .finish()
+    }
+}
+
+/// Cloning creates a new unborrowed `AtomicBorrow`.
+///
+/// The borrows of `self` belong to the data `self` guards, not to the clone of that data,
+/// so copying the state would make the clone look borrowed with nothing to ever release it.
+impl Clone for AtomicBorrow {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
+
 /// A guard that releases a shared reference when dropped.
 pub struct SharedGuard<'a, T> {
     data: *const T,
@@ -162,6 +633,9 @@ impl<'a, T> SharedGuard<'a, T> {
     /// # Safety
     /// * Any borrows of `data` must be registered with `borrow`.
     /// * `data` must be a valid pointer for the entire lifetime of `self`.
+    ///
+    /// [`AtomicRefCell`] upholds these by keeping the data next to its borrow, and
+    /// borrowing it safely.
     #[inline]
     pub unsafe fn try_new(data: *const T, borrow: &'a AtomicBorrow) -> Option<Self> {
         if borrow.borrow() {
@@ -183,8 +657,13 @@ impl<'a, T> SharedGuard<'a, T> {
     }
 
     /// Gets the inner [`AtomicBorrow`].
+    ///
+    /// # Safety
+    /// The borrow guards the data, which may belong to an [`AtomicRefCell`], so
+    /// references must not be released through it unless they were also acquired
+    /// through it.
     #[inline]
-    pub fn get_borrow(&self) -> &'a AtomicBorrow {
+    pub unsafe fn get_borrow(&self) -> &'a AtomicBorrow {
         self.borrow
     }
 
@@ -201,6 +680,64 @@ impl<'a, T> SharedGuard<'a, T> {
         std::mem::forget(self);
         ptr
     }
+
+    /// Releases the borrow now, the same as dropping `self`, but explicit at the call site.
+    #[inline]
+    pub fn release_now(self) {
+        drop(self);
+    }
+}
+
+impl<'a, T: Deref> SharedGuard<'a, T> {
+    /// Dereferences the data, eg. getting a `&str` from a guarded `String`.
+    ///
+    /// This is an associated function to avoid conflicting with methods on `T`.
+    #[inline]
+    pub fn as_deref(s: &Self) -> &T::Target {
+        T::deref(s)
+    }
+}
+
+impl<'a, T: Clone> SharedGuard<'a, T> {
+    /// Clones the data, while the borrow guarantees a consistent read.
+    ///
+    /// This is an associated function to avoid conflicting with methods on `T`.
+    #[inline]
+    pub fn cloned(s: &Self) -> T {
+        T::clone(s)
+    }
+
+    /// Clones the data into a new [`Arc`](std::sync::Arc), a snapshot that stays valid
+    /// after the borrow is released.
+    ///
+    /// This is an associated function to avoid conflicting with methods on `T`.
+    #[inline]
+    pub fn snapshot_arc(s: &Self) -> std::sync::Arc<T> {
+        std::sync::Arc::new(Self::cloned(s))
+    }
+}
+
+impl<'a, T: Copy> SharedGuard<'a, T> {
+    /// Copies the data, while the borrow guarantees a consistent read.
+    ///
+    /// This is an associated function to avoid conflicting with methods on `T`.
+    #[inline]
+    pub fn copied(s: &Self) -> T {
+        **s
+    }
+}
+
+impl<'a, T> SharedGuard<'a, MaybeUninit<T>> {
+    /// Converts to a guard of the initialized value, keeping the borrow.
+    ///
+    /// # Safety
+    /// * The data must be initialized.
+    #[inline]
+    pub unsafe fn assume_init(self) -> SharedGuard<'a, T> {
+        let borrow = self.borrow;
+        let data = self.forget() as *const T;
+        SharedGuard { data, borrow }
+    }
 }
 
 impl<'a, T> Deref for SharedGuard<'a, T> {
@@ -212,6 +749,13 @@ impl<'a, T> Deref for SharedGuard<'a, T> {
     }
 }
 
+impl<'a, T: fmt::Debug> fmt::Debug for SharedGuard<'a, T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
 impl<'a, T> Drop for SharedGuard<'a, T> {
     #[inline]
     fn drop(&mut self) {
@@ -219,6 +763,44 @@ impl<'a, T> Drop for SharedGuard<'a, T> {
     }
 }
 
+/// Reads from the start of the guarded slice.
+///
+/// A shared guard can't advance the slice it guards, so every read starts over from the
+/// beginning, and [`Read::read_to_end`] never returns for a non-empty slice. Use
+/// [`Read::read_exact`], or read from a copy of the slice with `let mut data = &**guard;`
+/// to read sequentially.
+impl<'a, 'b> Read for SharedGuard<'a, &'b [u8]> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut data: &[u8] = self;
+        data.read(buf)
+    }
+}
+
+impl<'a, T> Write for SharedGuard<'a, T>
+where
+    for<'b> &'b T: Write,
+{
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (&**self).write(buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        (&**self).flush()
+    }
+}
+
+impl<'a, T: Index<I>, I> Index<I> for SharedGuard<'a, T> {
+    type Output = T::Output;
+
+    #[inline]
+    fn index(&self, index: I) -> &Self::Output {
+        &(**self)[index]
+    }
+}
+
 /// A guard that releases a unique reference when dropped.
 pub struct UniqueGuard<'a, T> {
     data: *mut T,
@@ -237,6 +819,9 @@ impl<'a, T> UniqueGuard<'a, T> {
     /// # Safety
     /// * Any borrows of `data` must be registered with `borrow`.
     /// * `data` must be a valid pointer for the entire lifetime of `self`.
+    ///
+    /// [`AtomicRefCell`] upholds these by keeping the data next to its borrow, and
+    /// borrowing it safely.
     #[inline]
     pub unsafe fn try_new(data: *mut T, borrow: &'a AtomicBorrow) -> Option<Self> {
         if borrow.borrow_mut() {
@@ -258,8 +843,13 @@ impl<'a, T> UniqueGuard<'a, T> {
     }
 
     /// Gets the inner [`AtomicBorrow`].
+    ///
+    /// # Safety
+    /// The borrow guards the data, which may belong to an [`AtomicRefCell`], so
+    /// references must not be released through it unless they were also acquired
+    /// through it.
     #[inline]
-    pub fn get_borrow(&self) -> &'a AtomicBorrow {
+    pub unsafe fn get_borrow(&self) -> &'a AtomicBorrow {
         self.borrow
     }
 
@@ -276,6 +866,94 @@ impl<'a, T> UniqueGuard<'a, T> {
         std::mem::forget(self);
         ptr
     }
+
+    /// Gets a pinned mutable reference to the data, without consuming `self`.
+    ///
+    /// The guard itself never moves the data, but once the guard is dropped, the next
+    /// unique borrow could, which is why this is unsafe.
+    ///
+    /// # Safety
+    /// The data must not be moved for as long as it exists, even after `self` is dropped,
+    /// unless `T: Unpin`. See [`Pin::new_unchecked`].
+    #[inline]
+    pub unsafe fn as_pin_mut(&mut self) -> Pin<&mut T> {
+        // SAFETY: the caller guarantees the data stays pinned
+        unsafe { Pin::new_unchecked(&mut *self.data) }
+    }
+
+    /// Releases the borrow now, the same as dropping `self`, but explicit at the call site.
+    #[inline]
+    pub fn release_now(self) {
+        drop(self);
+    }
+
+    /// Runs `f` on the data as a final mutation, then releases the borrow.
+    #[inline]
+    pub fn release_now_with<R>(mut self, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut self)
+    }
+}
+
+impl<'a, T: Deref> UniqueGuard<'a, T> {
+    /// Dereferences the data, eg. getting a `&str` from a guarded `String`.
+    ///
+    /// This is an associated function to avoid conflicting with methods on `T`.
+    #[inline]
+    pub fn as_deref(s: &Self) -> &T::Target {
+        T::deref(s)
+    }
+}
+
+impl<'a, T: DerefMut> UniqueGuard<'a, T> {
+    /// Mutably dereferences the data, eg. getting a `&mut str` from a guarded `String`.
+    ///
+    /// This is an associated function to avoid conflicting with methods on `T`.
+    #[inline]
+    pub fn as_deref_mut(s: &mut Self) -> &mut T::Target {
+        T::deref_mut(s)
+    }
+}
+
+impl<'a, T: Clone> UniqueGuard<'a, T> {
+    /// Clones the data, while the borrow guarantees a consistent read.
+    ///
+    /// This is an associated function to avoid conflicting with methods on `T`.
+    #[inline]
+    pub fn cloned(s: &Self) -> T {
+        T::clone(s)
+    }
+}
+
+impl<'a, T: Copy> UniqueGuard<'a, T> {
+    /// Copies the data, while the borrow guarantees a consistent read.
+    ///
+    /// This is an associated function to avoid conflicting with methods on `T`.
+    #[inline]
+    pub fn copied(s: &Self) -> T {
+        **s
+    }
+}
+
+impl<'a, T> UniqueGuard<'a, MaybeUninit<T>> {
+    /// Initializes the data with `value`, returning a guard of the initialized value.
+    ///
+    /// The borrow is kept, any previous value is overwritten without being dropped.
+    #[inline]
+    pub fn write(mut self, value: T) -> UniqueGuard<'a, T> {
+        MaybeUninit::write(&mut *self, value);
+        unsafe { self.assume_init() }
+    }
+
+    /// Converts to a guard of the initialized value, keeping the borrow.
+    ///
+    /// # Safety
+    /// * The data must be initialized.
+    #[inline]
+    pub unsafe fn assume_init(self) -> UniqueGuard<'a, T> {
+        let borrow = self.borrow;
+        let data = self.forget() as *mut T;
+        UniqueGuard { data, borrow }
+    }
 }
 
 impl<'a, T> Deref for UniqueGuard<'a, T> {
@@ -294,6 +972,13 @@ impl<'a, T> DerefMut for UniqueGuard<'a, T> {
     }
 }
 
+impl<'a, T: fmt::Debug> fmt::Debug for UniqueGuard<'a, T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
 impl<'a, T> Drop for UniqueGuard<'a, T> {
     #[inline]
     fn drop(&mut self) {
@@ -301,10 +986,94 @@ impl<'a, T> Drop for UniqueGuard<'a, T> {
     }
 }
 
+impl<'a, T: Read> Read for UniqueGuard<'a, T> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        (**self).read(buf)
+    }
+}
+
+impl<'a, T: Write> Write for UniqueGuard<'a, T> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (**self).write(buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        (**self).flush()
+    }
+}
+
+impl<'a, T: fmt::Write> fmt::Write for UniqueGuard<'a, T> {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        (**self).write_str(s)
+    }
+
+    #[inline]
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        (**self).write_char(c)
+    }
+}
+
+impl<'a, T: Index<I>, I> Index<I> for UniqueGuard<'a, T> {
+    type Output = T::Output;
+
+    #[inline]
+    fn index(&self, index: I) -> &Self::Output {
+        &(**self)[index]
+    }
+}
+
+impl<'a, T: IndexMut<I>, I> IndexMut<I> for UniqueGuard<'a, T> {
+    #[inline]
+    fn index_mut(&mut self, index: I) -> &mut Self::Output {
+        &mut (**self)[index]
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// A waker counting how many times it was woken.
+    #[cfg(any(
+        feature = "state-stream",
+        all(
+            any(feature = "async", feature = "futures"),
+            not(feature = "single-threaded")
+        )
+    ))]
+    #[derive(Default)]
+    struct CountWaker(std::sync::atomic::AtomicUsize);
+
+    #[cfg(any(
+        feature = "state-stream",
+        all(
+            any(feature = "async", feature = "futures"),
+            not(feature = "single-threaded")
+        )
+    ))]
+    impl CountWaker {
+        fn count(&self) -> usize {
+            self.0.load(Ordering::SeqCst)
+        }
+    }
+
+    #[cfg(any(
+        feature = "state-stream",
+        all(
+            any(feature = "async", feature = "futures"),
+            not(feature = "single-threaded")
+        )
+    ))]
+    impl std::task::Wake for CountWaker {
+        fn wake(self: std::sync::Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
     #[test]
     fn atomic_borrow() {
         let borrow = AtomicBorrow::new();
@@ -323,4 +1092,2664 @@ mod tests {
 
         borrow.release_mut();
     }
+
+    #[test]
+    fn guard_io() {
+        let borrow = AtomicBorrow::new();
+
+        let mut buf: &[u8] = b"hello";
+        assert!(borrow.borrow_mut());
+        let mut guard = UniqueGuard::new(&mut buf, &borrow);
+        let mut out = [0; 5];
+        guard.read_exact(&mut out).unwrap();
+        assert_eq!(&out, b"hello");
+        drop(guard);
+
+        let mut vec = Vec::new();
+        assert!(borrow.borrow_mut());
+        let mut guard = UniqueGuard::new(&mut vec, &borrow);
+        guard.write_all(b"world").unwrap();
+        drop(guard);
+        assert_eq!(vec, b"world");
+
+        let data: &[u8] = b"guarded";
+        assert!(borrow.borrow());
+        let mut guard = SharedGuard::new(&data, &borrow);
+        let mut out = [0; 5];
+        guard.read_exact(&mut out).unwrap();
+        assert_eq!(&out, b"guard");
+        // the slice isn't advanced, reads start over
+        guard.read_exact(&mut out[..2]).unwrap();
+        assert_eq!(&out, b"guard");
+        assert_eq!(guard.read(&mut [0; 16]).unwrap(), 7);
+        drop(guard);
+
+        let sink = io::sink();
+        assert!(borrow.borrow());
+        let mut guard = SharedGuard::new(&sink, &borrow);
+        assert_eq!(guard.write(b"discarded").unwrap(), 9);
+        drop(guard);
+
+        assert!(!borrow.is_borrowed());
+    }
+
+    #[test]
+    #[cfg(not(feature = "single-threaded"))]
+    #[cfg(feature = "parking")]
+    fn blocking_borrow_mut_with() {
+        use std::{
+            sync::atomic::AtomicBool,
+            time::{Duration, Instant},
+        };
+
+        for spin_threshold in [0, 1 << 16, usize::MAX] {
+            let borrow = AtomicBorrow::new();
+            let started = AtomicBool::new(false);
+            assert!(borrow.borrow_mut());
+
+            std::thread::scope(|s| {
+                let waiter = s.spawn(|| {
+                    started.store(true, Ordering::SeqCst);
+                    borrow.blocking_borrow_mut_with(spin_threshold);
+                    borrow.release_mut();
+                });
+
+                while !started.load(Ordering::SeqCst) {
+                    std::thread::yield_now();
+                }
+
+                match spin_threshold {
+                    // parks right away, waking it just makes it park again
+                    0 => {
+                        let deadline = Instant::now() + Duration::from_secs(10);
+                        while !borrow.unpark_one() {
+                            assert!(Instant::now() < deadline, "waiter never parked");
+                            std::thread::sleep(Duration::from_millis(1));
+                        }
+                    }
+                    // still spinning while the holder sleeps
+                    usize::MAX => {
+                        std::thread::sleep(Duration::from_millis(10));
+                        assert!(!borrow.unpark_one());
+                    }
+                    _ => std::thread::sleep(Duration::from_millis(10)),
+                }
+
+                borrow.release_mut();
+                waiter.join().unwrap();
+            });
+
+            assert!(!borrow.is_borrowed());
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "single-threaded"))]
+    #[cfg(feature = "rayon")]
+    fn par_borrow() {
+        use rayon::iter::ParallelIterator;
+
+        let borrow = AtomicBorrow::new();
+        let data = (0..1000).collect::<Vec<u64>>();
+
+        let iter = borrow.par_borrow(&data).unwrap();
+        assert!(!borrow.borrow_mut());
+        assert_eq!(iter.sum::<u64>(), 499500);
+
+        assert!(!borrow.is_borrowed());
+    }
+
+    #[test]
+    #[cfg(not(feature = "single-threaded"))]
+    fn owned_shared_guard() {
+        struct Holder {
+            guard: OwnedSharedGuard<Vec<u32>>,
+        }
+
+        let guard = OwnedSharedGuard::new(vec![1, 2, 3]);
+        assert_eq!(guard.get_cell().state(), BorrowState::Shared(1));
+
+        let holder = Holder { guard };
+        let moved = std::thread::spawn(move || holder).join().unwrap();
+        assert_eq!(*moved.guard, [1, 2, 3]);
+
+        assert_eq!(moved.guard.into_inner(), [1, 2, 3]);
+
+        let guard = OwnedSharedGuard::from(1);
+        let rc = AtomicRc::new(2);
+        let shared = rc.borrow_owned().unwrap();
+        assert_eq!(*OwnedSharedGuard::into_owned_shared(3), 3);
+        assert_eq!(guard.try_into_inner().ok().unwrap(), 1);
+
+        // `rc` still holds the cell
+        let shared = shared.try_into_inner().err().unwrap();
+        assert_eq!(rc.state(), BorrowState::Shared(1));
+        drop(shared);
+        assert_eq!(rc.state(), BorrowState::Unborrowed);
+    }
+
+    #[test]
+    #[cfg(not(feature = "single-threaded"))]
+    fn atomic_rc() {
+        let rc = AtomicRc::new(1);
+
+        let mut guard = rc.borrow_mut_owned().unwrap();
+        assert!(rc.borrow_owned().is_none());
+        *guard += 1;
+        drop(guard);
+
+        let guard = rc.borrow_owned().unwrap();
+        drop(rc);
+
+        let guard = std::thread::spawn(move || guard).join().unwrap();
+        assert_eq!(*guard, 2);
+        assert_eq!(guard.get_cell().state(), BorrowState::Shared(1));
+    }
+
+    #[test]
+    fn debug_summary() {
+        let borrow = AtomicBorrow::new();
+        assert_eq!(
+            borrow.debug_summary(),
+            "AtomicBorrow { shared: 0, unique: false }"
+        );
+
+        borrow.borrow();
+        borrow.borrow();
+        borrow.borrow();
+        assert_eq!(
+            borrow.debug_summary(),
+            "AtomicBorrow { shared: 3, unique: false }"
+        );
+
+        borrow.release();
+        borrow.release();
+        borrow.release();
+        borrow.borrow_mut();
+        assert_eq!(
+            borrow.debug_summary(),
+            "AtomicBorrow { shared: 0, unique: true }"
+        );
+    }
+
+    #[test]
+    fn debug() {
+        let borrow = AtomicBorrow::new();
+        assert_eq!(
+            format!("{:?}", borrow),
+            "AtomicBorrow { shared: 0, unique: false }"
+        );
+
+        borrow.borrow();
+        borrow.borrow();
+        assert_eq!(
+            format!("{:?}", borrow),
+            "AtomicBorrow { shared: 2, unique: false }"
+        );
+
+        borrow.release();
+        borrow.release();
+        borrow.borrow_mut();
+        assert_eq!(
+            format!("{:?}", borrow),
+            "AtomicBorrow { shared: 0, unique: true }"
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "single-threaded"))]
+    fn cached_borrow() {
+        let borrow = AtomicBorrow::new();
+
+        assert!(borrow.cached_borrow());
+        assert!(borrow.cached_borrow());
+        assert!(borrow.cached_borrow());
+        assert_eq!(borrow.shared_count(), 1);
+
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                assert!(borrow.cached_borrow());
+                assert_eq!(borrow.shared_count(), 2);
+                borrow.cached_release();
+            });
+        });
+
+        borrow.cached_release();
+        borrow.cached_release();
+        assert_eq!(borrow.shared_count(), 1);
+
+        borrow.cached_release();
+        assert!(!borrow.is_borrowed());
+    }
+
+    #[test]
+    fn checked() {
+        let borrow = AtomicBorrow::new();
+
+        assert_eq!(borrow.checked_release(), Err(BorrowError::NotBorrowed));
+        assert_eq!(
+            borrow.checked_release_mut(),
+            Err(BorrowError::NotUniquelyBorrowed)
+        );
+
+        assert_eq!(borrow.checked_borrow(), Ok(()));
+        assert_eq!(
+            borrow.checked_borrow_mut(),
+            Err(BorrowMutError::SharedBorrowed)
+        );
+        assert_eq!(
+            borrow.checked_release_mut(),
+            Err(BorrowError::NotUniquelyBorrowed)
+        );
+        assert_eq!(borrow.checked_release(), Ok(()));
+
+        assert_eq!(borrow.checked_borrow_mut(), Ok(()));
+        assert_eq!(borrow.checked_borrow(), Err(BorrowError::UniquelyBorrowed));
+        assert_eq!(
+            borrow.checked_borrow_mut(),
+            Err(BorrowMutError::UniquelyBorrowed)
+        );
+        assert_eq!(borrow.checked_release(), Err(BorrowError::UniquelyBorrowed));
+        assert_eq!(borrow.checked_release_mut(), Ok(()));
+        assert!(!borrow.is_borrowed());
+
+        borrow
+            .borrow
+            .store(AtomicBorrow::SHARED_MASK, Ordering::Relaxed);
+        assert_eq!(borrow.checked_borrow(), Err(BorrowError::Overflow));
+        assert_eq!(borrow.shared_count(), AtomicBorrow::SHARED_MASK);
+    }
+
+    #[test]
+    #[cfg(not(feature = "single-threaded"))]
+    fn spin_borrow_mut_jittered() {
+        let borrow = AtomicBorrow::new();
+        let counter = AtomicUsize::new(0);
+
+        std::thread::scope(|s| {
+            for _ in 0..64 {
+                s.spawn(|| {
+                    for _ in 0..100 {
+                        borrow.spin_borrow_mut_jittered();
+                        let count = counter.load(Ordering::Relaxed);
+                        counter.store(count + 1, Ordering::Relaxed);
+                        borrow.release_mut();
+                    }
+                });
+            }
+        });
+
+        assert_eq!(counter.load(Ordering::Relaxed), 6400);
+        assert!(!borrow.is_borrowed());
+    }
+
+    #[test]
+    #[cfg(not(feature = "single-threaded"))]
+    fn adaptive_borrow_mut() {
+        let borrow = AdaptiveBorrow::new();
+        let initial = borrow.spin_budget();
+
+        assert!(borrow.borrow_mut());
+        std::thread::scope(|s| {
+            let waiter = s.spawn(|| {
+                borrow.adaptive_borrow_mut();
+                borrow.release_mut();
+            });
+
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            borrow.release_mut();
+            waiter.join().unwrap();
+        });
+
+        assert!(borrow.spin_budget() < initial);
+        assert!(!borrow.is_borrowed());
+    }
+
+    #[test]
+    fn maybe_uninit_write() {
+        let borrow = AtomicBorrow::new();
+        let mut data = MaybeUninit::<String>::uninit();
+
+        assert!(borrow.borrow_mut());
+        let guard = UniqueGuard::new(&mut data, &borrow);
+        let mut guard = guard.write(String::from("hello"));
+        guard.push_str(" world");
+        assert_eq!(*guard, "hello world");
+        drop(guard);
+        assert!(!borrow.is_borrowed());
+
+        assert!(borrow.borrow());
+        let guard = unsafe { SharedGuard::new(&data, &borrow).assume_init() };
+        assert_eq!(*guard, "hello world");
+        drop(guard);
+
+        unsafe { data.assume_init_drop() };
+    }
+
+    #[test]
+    #[cfg(not(feature = "single-threaded"))]
+    #[cfg(feature = "diagnostics")]
+    fn convoy() {
+        use std::sync::Arc;
+
+        let detected = Arc::new(AtomicUsize::new(0));
+        let borrow = {
+            let detected = detected.clone();
+            ConvoyBorrow::new(4, std::time::Duration::from_secs(1), move |handoffs| {
+                detected.store(handoffs, Ordering::Relaxed);
+            })
+        };
+
+        // the same thread reacquiring isn't a handoff
+        for _ in 0..8 {
+            assert!(borrow.borrow_mut());
+            borrow.release_mut();
+        }
+        assert_eq!(detected.load(Ordering::Relaxed), 0);
+
+        // alternate between two threads
+        for _ in 0..2 {
+            std::thread::scope(|s| {
+                s.spawn(|| {
+                    assert!(borrow.borrow_mut());
+                    borrow.release_mut();
+                });
+            });
+
+            assert!(borrow.borrow_mut());
+            borrow.release_mut();
+        }
+        assert_eq!(detected.load(Ordering::Relaxed), 4);
+    }
+
+    #[test]
+    fn mapped_guards() {
+        let cell = AtomicRefCell::new((1, vec![2, 3]));
+
+        let guard = cell.try_borrow().ok().unwrap();
+        let guard = SharedGuard::map(guard, |(_, vec)| vec);
+        let guard = MappedSharedGuard::map(guard, |vec| vec.as_slice());
+        let guard = MappedSharedGuard::try_map(guard, |slice| slice.get(5))
+            .err()
+            .unwrap();
+        let guard = MappedSharedGuard::try_map(guard, |slice| slice.first())
+            .ok()
+            .unwrap();
+        assert_eq!(*guard, 2);
+        assert_eq!(cell.state(), BorrowState::Shared(1));
+        drop(guard);
+        assert_eq!(cell.state(), BorrowState::Unborrowed);
+
+        let guard = cell.try_borrow_mut().ok().unwrap();
+        let guard = UniqueGuard::try_map(guard, |_| None::<&mut i32>)
+            .err()
+            .unwrap();
+        let guard = UniqueGuard::map(guard, |(_, vec)| vec);
+        let mut guard = MappedUniqueGuard::map(guard, |vec| vec.as_mut_slice());
+        guard[0] = 4;
+        assert!(cell.try_borrow().is_err());
+        drop(guard);
+        assert_eq!(cell.state(), BorrowState::Unborrowed);
+
+        assert_eq!(cell.into_inner(), (1, vec![4, 3]));
+    }
+
+    #[test]
+    fn mapped_guards_unsized() {
+        let cell = AtomicRefCell::new((String::from("hello"), vec![1, 2]));
+
+        let guard = cell.try_borrow().ok().unwrap();
+        let guard: MappedSharedGuard<str> = SharedGuard::map(guard, |(s, _)| s.as_str());
+        assert_eq!(&*guard, "hello");
+        drop(guard);
+
+        let guard = cell.try_borrow_mut().ok().unwrap();
+        let mut guard: MappedUniqueGuard<[i32]> =
+            UniqueGuard::map(guard, |(_, vec)| vec.as_mut_slice());
+        guard[1] = 3;
+        drop(guard);
+
+        let guard = cell.try_borrow().ok().unwrap();
+        let guard = SharedGuard::try_map(guard, |(_, vec)| vec.get(..1))
+            .ok()
+            .unwrap();
+        assert_eq!(&*guard, &[1]);
+        drop(guard);
+        assert_eq!(cell.state(), BorrowState::Unborrowed);
+
+        assert_eq!(cell.into_inner().1, vec![1, 3]);
+    }
+
+    #[test]
+    fn map_ref() {
+        let cell = AtomicRefCell::new((1, vec![2, 3]));
+        let sum = |slice: &[i32]| slice.iter().sum::<i32>();
+
+        let mut guard = cell.borrow_mut();
+        let vec = UniqueGuard::map_ref(&guard, |(_, vec)| vec.as_slice());
+        assert_eq!(sum(vec), 5);
+
+        guard.0 = sum(UniqueGuard::map_ref(&guard, |(_, vec)| vec));
+        guard.1.push(4);
+        assert_eq!(*UniqueGuard::map_ref(&guard, |(a, _)| a), 5);
+        assert!(cell.try_borrow().is_err());
+        drop(guard);
+
+        assert_eq!(cell.into_inner(), (5, vec![2, 3, 4]));
+    }
+
+    #[test]
+    fn revocable_borrow() {
+        let borrow = RevocableBorrow::new();
+
+        assert!(borrow.borrow());
+        assert!(borrow.borrow());
+
+        borrow.revoke();
+        assert!(borrow.is_revoked());
+        assert!(!borrow.borrow());
+        assert!(!borrow.borrow_mut());
+        // failed borrows leave the count alone
+        assert_eq!(borrow.state(), BorrowState::Shared(2));
+        assert_eq!(borrow.shared_count(), 2);
+
+        borrow.release();
+        borrow.release();
+        assert!(!borrow.is_borrowed());
+        assert!(!borrow.borrow_mut());
+    }
+
+    #[test]
+    #[cfg(not(debug_assertions))]
+    fn release_without_debug_checks() {
+        // the debug checks must be compiled out entirely, so misuse goes unchecked
+        let borrow = std::hint::black_box(AtomicBorrow::new());
+
+        borrow.release_mut();
+        assert!(!borrow.is_borrowed());
+
+        assert!(borrow.borrow_mut());
+        borrow.release();
+        assert_eq!(
+            borrow.borrow.load(Ordering::Relaxed),
+            AtomicBorrow::SHARED_MASK
+        );
+    }
+
+    #[test]
+    fn borrow_n() {
+        const _: () = AtomicBorrow::assert_capacity::<{ AtomicBorrow::SHARED_MASK }>();
+
+        let borrow = AtomicBorrow::new();
+
+        assert!(borrow.borrow_n::<2>());
+        assert!(borrow.borrow_n::<2>());
+        assert!(!borrow.borrow_n::<2>());
+        assert!(borrow.borrow_n::<3>());
+
+        borrow.release();
+        borrow.release();
+        borrow.release();
+
+        assert!(borrow.borrow_mut());
+        assert!(!borrow.borrow_n::<2>());
+        borrow.release_mut();
+
+        #[cfg(feature = "test-failpoints")]
+        {
+            borrow.fail_next_borrow();
+            assert!(!borrow.borrow_n::<2>());
+            assert!(!borrow.is_borrowed());
+            assert!(borrow.borrow_n::<2>());
+            borrow.release();
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "single-threaded"))]
+    fn scope_with_shared() {
+        let borrow = AtomicBorrow::new();
+        let data = vec![1, 2, 3, 4];
+
+        let sum = borrow.scope_with_shared(&data, |scope, guard| {
+            let handles = (0..4)
+                .map(|i| {
+                    let guard = guard.clone();
+                    scope.spawn(move || guard[i])
+                })
+                .collect::<Vec<_>>();
+
+            assert!(!guard.get_borrow().borrow_mut());
+
+            handles.into_iter().map(|h| h.join().unwrap()).sum::<i32>()
+        });
+
+        assert_eq!(sum, 10);
+        assert!(!borrow.is_borrowed());
+    }
+
+    #[test]
+    fn try_borrow_array() {
+        let borrows = [
+            AtomicBorrow::new(),
+            AtomicBorrow::new(),
+            AtomicBorrow::new(),
+        ];
+        let datas = [1, 2, 3];
+
+        let borrow_refs = [&borrows[0], &borrows[1], &borrows[2]];
+        let data_ptrs = [&datas[0] as *const i32, &datas[1], &datas[2]];
+
+        let guards = unsafe { AtomicBorrow::try_borrow_array(&borrow_refs, &data_ptrs) }.unwrap();
+        assert_eq!(guards.iter().map(|g| **g).sum::<i32>(), 6);
+        assert!(borrows.iter().all(|b| b.shared_count() == 1));
+        drop(guards);
+
+        assert!(borrows[2].borrow_mut());
+        let guards = unsafe { AtomicBorrow::try_borrow_array(&borrow_refs, &data_ptrs) };
+        assert!(guards.is_none());
+        assert!(!borrows[0].is_borrowed());
+        assert!(!borrows[1].is_borrowed());
+        borrows[2].release_mut();
+    }
+
+    #[test]
+    #[cfg(not(feature = "single-threaded"))]
+    #[cfg(feature = "async")]
+    fn borrow_mut_async() {
+        use std::{
+            future::Future,
+            sync::Arc,
+            task::{Context, Waker},
+        };
+
+        let count = Arc::new(CountWaker::default());
+        let waker = Waker::from(count.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        let borrow = AtomicBorrow::new();
+        assert!(borrow.borrow());
+
+        let mut future = std::pin::pin!(borrow.borrow_mut_async());
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(count.count(), 0);
+
+        borrow.release();
+        assert_eq!(count.count(), 1);
+
+        assert!(future.as_mut().poll(&mut cx).is_ready());
+        assert!(borrow.is_borrowed());
+        borrow.release_mut();
+
+        // dropped futures don't leave their wakers behind
+        assert!(borrow.borrow());
+        let mut future = Box::pin(borrow.borrow_mut_async());
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+        drop(future);
+        let mut future = Box::pin(borrow.when_free());
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+        drop(future);
+        borrow.release();
+        assert_eq!(count.count(), 1);
+    }
+
+    #[test]
+    #[cfg(not(feature = "single-threaded"))]
+    #[cfg(feature = "async")]
+    fn failed_borrow_wakes_waiters() {
+        use std::{
+            future::Future,
+            sync::Arc,
+            task::{Context, Waker},
+        };
+
+        let count = Arc::new(CountWaker::default());
+        let waker = Waker::from(count.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        let borrow = AtomicBorrow::new();
+        assert!(borrow.borrow_mut());
+
+        let mut future = std::pin::pin!(borrow.borrow_mut_async());
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+
+        // a failing `borrow` has incremented, but not yet undone, when the unique
+        // reference is released
+        let prev = borrow.borrow.fetch_add(1, Ordering::Acquire);
+        borrow.release_mut();
+        assert_eq!(count.count(), 0);
+
+        assert!(!borrow.borrow_failed(prev));
+        assert_eq!(count.count(), 1);
+        assert!(future.as_mut().poll(&mut cx).is_ready());
+        borrow.release_mut();
+    }
+
+    #[test]
+    #[cfg(not(feature = "single-threaded"))]
+    #[cfg(feature = "async")]
+    fn when_free() {
+        use std::{
+            future::Future,
+            sync::Arc,
+            task::{Context, Waker},
+        };
+
+        let count = Arc::new(CountWaker::default());
+        let waker = Waker::from(count.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        let borrow = AtomicBorrow::new();
+        assert!(std::pin::pin!(borrow.when_free()).poll(&mut cx).is_ready());
+
+        assert!(borrow.borrow());
+        assert!(borrow.borrow());
+
+        let mut future = std::pin::pin!(borrow.when_free());
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+
+        borrow.release();
+        assert_eq!(count.count(), 0);
+
+        borrow.release();
+        assert_eq!(count.count(), 1);
+
+        assert!(future.as_mut().poll(&mut cx).is_ready());
+        assert!(!borrow.is_borrowed());
+    }
+
+    #[test]
+    #[cfg(not(feature = "single-threaded"))]
+    fn swmr_borrow() {
+        let borrow = SwmrBorrow::new();
+        let data = AtomicUsize::new(0);
+
+        // a failing writer leaves no trace, and readers fail while it writes
+        assert!(borrow.reader_borrow());
+        // SAFETY: this is the only writer
+        assert!(!unsafe { borrow.writer_borrow_mut() });
+        assert!(!borrow.is_writing());
+        assert_eq!(borrow.shared_count(), 1);
+        borrow.reader_release();
+
+        // SAFETY: this is the only writer
+        assert!(unsafe { borrow.writer_borrow_mut() });
+        assert!(borrow.is_writing());
+        assert!(!borrow.reader_borrow());
+        assert_eq!(borrow.shared_count(), 0);
+        // SAFETY: the writer holds the reference
+        unsafe { borrow.writer_release_mut() };
+        assert!(!borrow.is_borrowed());
+
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..1000 {
+                        if borrow.reader_borrow() {
+                            // the writer only ever writes even values while holding unique
+                            assert_eq!(data.load(Ordering::Relaxed) % 2, 0);
+                            borrow.reader_release();
+                        }
+                    }
+                });
+            }
+
+            s.spawn(|| {
+                for _ in 0..1000 {
+                    // SAFETY: this is the only writer thread
+                    if unsafe { borrow.writer_borrow_mut() } {
+                        data.fetch_add(1, Ordering::Relaxed);
+                        data.fetch_add(1, Ordering::Relaxed);
+                        // SAFETY: the writer holds the reference
+                        unsafe { borrow.writer_release_mut() };
+                    }
+                }
+            });
+        });
+
+        assert!(!borrow.is_borrowed());
+    }
+
+    #[test]
+    fn lease_borrow_mut() {
+        use std::time::Duration;
+
+        let lease = LeaseBorrow::new();
+
+        let guard = lease.lease_borrow_mut(Duration::from_secs(60)).unwrap();
+        assert!(lease.lease_borrow_mut(Duration::ZERO).is_none());
+        assert!(!lease.reap_expired());
+        assert!(guard.is_valid());
+        drop(guard);
+        assert!(!lease.get_borrow().is_borrowed());
+
+        let leaked = lease.lease_borrow_mut(Duration::ZERO).unwrap();
+        assert!(lease.reap_expired());
+        assert!(!leaked.is_valid());
+        assert!(!lease.get_borrow().is_borrowed());
+
+        let guard = lease.lease_borrow_mut(Duration::from_secs(60)).unwrap();
+        // dropping the reaped lease must not release the new one
+        drop(leaked);
+        assert!(lease.get_borrow().is_borrowed());
+        drop(guard);
+        assert!(!lease.get_borrow().is_borrowed());
+    }
+
+    #[test]
+    #[cfg(not(feature = "single-threaded"))]
+    #[cfg(feature = "futures")]
+    fn notify_borrow() {
+        use std::{sync::Arc, task::Waker};
+
+        let count = Arc::new(CountWaker::default());
+        let waker = Waker::from(count.clone());
+
+        let borrow = NotifyBorrow::new();
+        assert!(borrow.borrow());
+        assert!(borrow.borrow());
+
+        borrow.register_waker(&waker);
+        borrow.release();
+        assert_eq!(count.count(), 0);
+        assert_eq!(borrow.state(), BorrowState::Shared(1));
+        borrow.release();
+        assert_eq!(count.count(), 1);
+
+        assert!(borrow.borrow_mut());
+        assert_eq!(borrow.shared_count(), 0);
+        borrow.register_waker(&waker);
+        borrow.release_mut();
+        assert_eq!(count.count(), 2);
+        assert!(!borrow.is_borrowed());
+    }
+
+    #[test]
+    fn signal_safe() {
+        let borrow = SignalSafeBorrow::new();
+
+        assert!(borrow.borrow());
+        assert!(!borrow.borrow_mut());
+        assert_eq!(borrow.shared_count(), 1);
+        borrow.release();
+
+        assert!(borrow.borrow_mut());
+        assert!(!borrow.borrow());
+        borrow.release_mut();
+
+        assert!(!borrow.is_borrowed());
+    }
+
+    #[test]
+    fn from_c_ptr() {
+        #[repr(C)]
+        struct Shared {
+            tag: u32,
+            borrow: usize,
+        }
+
+        let mut shared = Shared { tag: 7, borrow: 0 };
+        let ptr = std::ptr::addr_of_mut!(shared.borrow);
+
+        let borrow = unsafe { AtomicBorrow::from_c_ptr(ptr) };
+        assert_eq!(borrow.as_ptr(), ptr);
+        assert!(borrow.borrow_mut());
+        assert_eq!(unsafe { ptr.read() }, AtomicBorrow::UNIQUE_MASK);
+        borrow.release_mut();
+
+        assert_eq!(shared.borrow, 0);
+        assert_eq!(shared.tag, 7);
+    }
+
+    #[test]
+    #[cfg(feature = "futures")]
+    fn drain_stream() {
+        use futures_util::{task::noop_waker_ref, Stream, StreamExt};
+        use std::{collections::VecDeque, task::Context};
+
+        let cell = AtomicRefCell::new(VecDeque::from([1, 2, 3]));
+        let mut cx = Context::from_waker(noop_waker_ref());
+
+        let mut stream = cell.try_borrow_mut().ok().unwrap().drain_stream();
+        assert_eq!(stream.size_hint(), (3, Some(3)));
+
+        let mut items = Vec::new();
+        while let std::task::Poll::Ready(Some(item)) = stream.poll_next_unpin(&mut cx) {
+            items.push(item);
+            assert!(cell.try_borrow().is_err());
+        }
+        assert_eq!(items, [1, 2, 3]);
+
+        drop(stream);
+        assert_eq!(cell.state(), BorrowState::Unborrowed);
+    }
+
+    #[test]
+    #[cfg(not(feature = "single-threaded"))]
+    fn double_buffer() {
+        let buffer = AtomicDoubleBuffer::new([0; 64], [0; 64]);
+
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..1000 {
+                        // readers are never turned away by a swap
+                        let front = buffer.read().unwrap();
+                        assert!(front.iter().all(|&x| x == front[0]));
+                    }
+                });
+            }
+
+            s.spawn(|| {
+                for i in 1..=1000 {
+                    if let Some(mut back) = buffer.write() {
+                        back.fill(i);
+                        buffer.swap(back);
+                    }
+                }
+            });
+        });
+
+        let (front, _) = buffer.into_inner();
+        assert!(front.iter().all(|&x| x == front[0]));
+    }
+
+    #[test]
+    #[should_panic(expected = "guard doesn't borrow the back buffer")]
+    fn double_buffer_swap_foreign_guard() {
+        let buffer = AtomicDoubleBuffer::new(0, 0);
+        let other = AtomicRefCell::new(0);
+        buffer.swap(other.borrow_mut());
+    }
+
+    #[test]
+    fn is_at_capacity() {
+        let borrow = AtomicBorrow::new();
+        assert!(!borrow.is_at_capacity());
+
+        borrow
+            .borrow
+            .store(AtomicBorrow::SHARED_MASK - 2, Ordering::Relaxed);
+        assert!(!borrow.is_at_capacity());
+
+        assert!(borrow.borrow());
+        assert!(borrow.is_at_capacity());
+        assert_eq!(
+            borrow.shared_count_saturating(),
+            AtomicBorrow::SHARED_MASK - 1
+        );
+
+        borrow.release();
+        assert!(!borrow.is_at_capacity());
+    }
+
+    #[test]
+    #[cfg(not(feature = "single-threaded"))]
+    fn on_last_release() {
+        use std::sync::Arc;
+
+        let borrow = ReclaimBorrow::new();
+        let fired = Arc::new(AtomicUsize::new(0));
+
+        assert!(borrow.borrow());
+        assert!(borrow.borrow());
+
+        let hook_fired = fired.clone();
+        borrow.on_last_release(move || {
+            hook_fired.fetch_add(1, Ordering::Relaxed);
+        });
+
+        borrow.release();
+        assert_eq!(fired.load(Ordering::Relaxed), 0);
+        borrow.release();
+        assert_eq!(fired.load(Ordering::Relaxed), 1);
+
+        assert!(borrow.borrow());
+        borrow.release();
+        assert_eq!(fired.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    #[cfg(not(feature = "single-threaded"))]
+    fn reclaim_hook_holds_borrow() {
+        use std::sync::Arc;
+
+        let borrow = Arc::new(ReclaimBorrow::new());
+        let fired = Arc::new(AtomicUsize::new(0));
+
+        let hook_borrow = borrow.clone();
+        let hook_fired = fired.clone();
+        borrow.on_last_release(move || {
+            // readers are locked out until the hook returns
+            assert!(!hook_borrow.borrow());
+            assert!(hook_borrow.is_borrowed());
+            hook_fired.fetch_add(1, Ordering::Relaxed);
+        });
+
+        assert!(borrow.borrow());
+        borrow.release();
+        assert_eq!(fired.load(Ordering::Relaxed), 1);
+        assert!(!borrow.is_borrowed());
+    }
+
+    #[test]
+    fn cow_borrow() {
+        let cow = CowBorrow::new(vec![1, 2, 3]);
+
+        // no readers, mutated in place
+        cow.make_mut().unwrap().push(4);
+
+        let old = cow.read().unwrap();
+        let mut new = cow.make_mut().unwrap();
+        new.push(5);
+
+        assert!(cow.read().is_none());
+        assert!(cow.make_mut().is_none());
+        assert_eq!(*old, [1, 2, 3, 4]);
+        drop(new);
+
+        assert_eq!(*cow.read().unwrap(), [1, 2, 3, 4, 5]);
+        assert_eq!(*old, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "single-threaded"))]
+    #[cfg(feature = "parking")]
+    fn park_borrow_mut_timeout() {
+        use std::time::Duration;
+
+        let borrow = AtomicBorrow::new();
+        assert!(borrow.borrow());
+
+        assert!(!borrow.park_borrow_mut_timeout(Duration::from_millis(10)));
+
+        std::thread::scope(|s| {
+            let waiter = s.spawn(|| borrow.park_borrow_mut_timeout(Duration::from_secs(60)));
+
+            std::thread::sleep(Duration::from_millis(10));
+            borrow.release();
+            assert!(waiter.join().unwrap());
+        });
+
+        borrow.release_mut();
+    }
+
+    #[test]
+    fn error_display_without_alloc() {
+        use core::fmt::Write;
+
+        struct Buf([u8; 32], usize);
+
+        impl Write for Buf {
+            fn write_str(&mut self, s: &str) -> core::fmt::Result {
+                let end = self.1 + s.len();
+                self.0.get_mut(self.1..end).ok_or(core::fmt::Error)?;
+                self.0[self.1..end].copy_from_slice(s.as_bytes());
+                self.1 = end;
+                Ok(())
+            }
+        }
+
+        let mut buf = Buf([0; 32], 0);
+        write!(buf, "{}", BorrowError::Overflow).unwrap();
+        assert_eq!(&buf.0[..buf.1], b"borrow counter overflowed");
+
+        let mut buf = Buf([0; 32], 0);
+        write!(buf, "{}", BorrowMutError::SharedBorrowed).unwrap();
+        assert_eq!(&buf.0[..buf.1], b"already shared borrowed");
+
+        assert_eq!(std::mem::size_of::<BorrowError>(), 1);
+        assert_eq!(
+            BorrowError::from(BorrowMutError::UniquelyBorrowed),
+            BorrowError::UniquelyBorrowed
+        );
+    }
+
+    #[test]
+    fn guard_batch() {
+        let borrow = AtomicBorrow::new();
+        let other = AtomicBorrow::new();
+        let data = [1, 2, 3];
+
+        let guards = data
+            .iter()
+            .map(|x| unsafe { SharedGuard::try_new(x, &borrow) }.unwrap())
+            .collect::<Vec<_>>();
+        let batch = SharedGuard::into_batch(guards).ok().unwrap();
+        assert_eq!(batch.iter().sum::<i32>(), 6);
+        assert_eq!(borrow.shared_count(), 3);
+        drop(batch);
+        assert!(!borrow.is_borrowed());
+
+        let guards = vec![
+            unsafe { SharedGuard::try_new(&data[0], &borrow) }.unwrap(),
+            unsafe { SharedGuard::try_new(&data[1], &other) }.unwrap(),
+        ];
+        let guards = SharedGuard::into_batch(guards).err().unwrap();
+        assert_eq!(guards.len(), 2);
+        drop(guards);
+        assert!(!borrow.is_borrowed());
+        assert!(!other.is_borrowed());
+
+        let guards = data
+            .iter()
+            .map(|x| unsafe { SharedGuard::try_new(x, &borrow) }.unwrap())
+            .collect::<Vec<_>>();
+        let mut guards = SharedGuard::into_batch(guards).ok().unwrap().into_guards();
+        guards.pop();
+        assert_eq!(borrow.shared_count(), 2);
+    }
+
+    #[test]
+    fn ref_cell_api() {
+        let cell = AtomicRefCell::new(1);
+
+        let a = cell.borrow();
+        let b = cell.borrow();
+        assert_eq!(*a + *b, 2);
+        assert_eq!(
+            cell.try_borrow_mut().err(),
+            Some(BorrowMutError::SharedBorrowed)
+        );
+        drop((a, b));
+
+        let mut guard = cell.borrow_mut();
+        *guard = 2;
+        assert_eq!(cell.try_borrow().err(), Some(BorrowError::UniquelyBorrowed));
+        assert_eq!(
+            cell.try_borrow_mut().err(),
+            Some(BorrowMutError::UniquelyBorrowed)
+        );
+        drop(guard);
+
+        assert_eq!(cell.replace(3), 2);
+        assert_eq!(cell.replace_with(|x| *x * 2), 3);
+        assert_eq!(cell.take(), 6);
+
+        let other = AtomicRefCell::new(4);
+        cell.swap(&other);
+        assert_eq!((*cell.borrow(), *other.borrow()), (4, 0));
+
+        let mut cell = cell;
+        *cell.get_mut() += 1;
+        assert_eq!(cell.into_inner(), 5);
+
+        let cell = AtomicRefCell::new(0);
+        let guard = cell.borrow_mut();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| cell.borrow()));
+        assert!(result.is_err());
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| cell.replace(1)));
+        assert!(result.is_err());
+        drop(guard);
+
+        let guard = cell.borrow();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| cell.borrow_mut()));
+        assert!(result.is_err());
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| cell.take()));
+        assert!(result.is_err());
+        drop(guard);
+        assert_eq!(cell.state(), BorrowState::Unborrowed);
+    }
+
+    #[test]
+    #[cfg(not(feature = "single-threaded"))]
+    #[cfg(feature = "hdrhistogram")]
+    fn record_into() {
+        use hdrhistogram::Histogram;
+        use std::{sync::Mutex, time::Duration};
+
+        let borrow = AtomicBorrow::new();
+        let hist = Mutex::new(Histogram::<u64>::new(3).unwrap());
+
+        assert!(borrow.borrow_mut());
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    borrow.record_into(&hist);
+                    borrow.release_mut();
+                });
+            }
+
+            std::thread::sleep(Duration::from_millis(10));
+            borrow.release_mut();
+        });
+
+        let hist = hist.into_inner().unwrap();
+        assert_eq!(hist.len(), 4);
+        assert!(hist.max() > 0);
+        assert!(!borrow.is_borrowed());
+    }
+
+    #[test]
+    fn release_sink() {
+        let shared = AtomicBorrow::new();
+        let unique = AtomicBorrow::new();
+        let mut a = 1;
+        let mut b = 2;
+
+        let sink = ReleaseSink::new();
+        let guard = unsafe { SharedGuard::try_new(&a, &shared) }.unwrap();
+        guard.transfer_release_to(&sink);
+        let guard = unsafe { UniqueGuard::try_new(&mut b, &unique) }.unwrap();
+        guard.transfer_release_to(&sink);
+
+        assert_eq!(sink.len(), 2);
+        assert_eq!(shared.shared_count(), 1);
+        assert!(!unique.borrow());
+
+        sink.flush();
+        assert!(sink.is_empty());
+        assert!(!shared.is_borrowed());
+        assert!(!unique.is_borrowed());
+
+        let guard = unsafe { UniqueGuard::try_new(&mut a, &unique) }.unwrap();
+        guard.transfer_release_to(&sink);
+        assert!(unique.is_borrowed());
+        drop(sink);
+        assert!(!unique.is_borrowed());
+    }
+
+    #[test]
+    fn relaxed_borrow() {
+        let borrow = RelaxedBorrow::new();
+
+        assert!(borrow.borrow());
+        assert!(borrow.borrow());
+        assert!(!borrow.borrow_mut());
+        assert_eq!(borrow.shared_count(), 2);
+        borrow.release();
+        borrow.release();
+
+        assert!(borrow.borrow_mut());
+        assert!(!borrow.borrow());
+        assert!(!borrow.borrow_mut());
+        borrow.release_mut();
+        assert!(!borrow.is_borrowed());
+    }
+
+    #[test]
+    fn reborrow_shared() {
+        fn sum(data: SharedRef<'_, Vec<i32>>) -> i32 {
+            data.iter().sum()
+        }
+
+        let cell = AtomicRefCell::new(vec![1, 2]);
+        let mut guard = cell.borrow_mut();
+
+        let shared = guard.reborrow_shared();
+        let copy = shared;
+        assert_eq!(sum(shared) + sum(copy), 6);
+        assert!(cell.try_borrow().is_err());
+
+        guard.push(3);
+        assert_eq!(sum(guard.reborrow_shared()), 6);
+        drop(guard);
+        assert_eq!(cell.state(), BorrowState::Unborrowed);
+    }
+
+    #[test]
+    fn find_free_slots() {
+        let array = AtomicBorrowArray::new(200);
+
+        // hold the first two chunks entirely, and every other slot after that
+        for (i, borrow) in array.iter().enumerate() {
+            if i < 16 || i % 2 == 0 {
+                assert!(borrow.borrow_mut());
+            }
+        }
+
+        let slots = array.find_free_slots(3).unwrap();
+        assert_eq!(slots, [17, 19, 21]);
+        assert_eq!(array.find_free_slots(92).unwrap().len(), 92);
+        assert!(array.find_free_slots(93).is_none());
+        assert_eq!(array.find_free_slots(0), Some(Vec::new()));
+
+        for slot in slots {
+            assert!(array[slot].borrow_mut());
+        }
+        assert_eq!(array.find_free_slots(1), Some(vec![23]));
+
+        // the only free slot is the very last one, so the scan runs over every chunk,
+        // including the final ones with nothing left to prefetch ahead of them
+        let array = AtomicBorrowArray::new(1000);
+        for borrow in &array[..999] {
+            assert!(borrow.borrow());
+        }
+        assert_eq!(array.find_free_slots(1), Some(vec![999]));
+    }
+
+    #[test]
+    fn as_pin_mut() {
+        use std::{
+            future::Future,
+            task::{Context, Poll, Waker},
+        };
+
+        let cell = AtomicRefCell::new(async {
+            std::future::ready(()).await;
+            1
+        });
+        let mut cx = Context::from_waker(Waker::noop());
+
+        let mut guard = cell.borrow_mut();
+        // SAFETY: the future is never moved out of the cell
+        let poll = unsafe { guard.as_pin_mut() }.poll(&mut cx);
+        assert_eq!(poll, Poll::Ready(1));
+        drop(guard);
+        assert_eq!(cell.state(), BorrowState::Unborrowed);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_round_trip() {
+        let cell = AtomicRefCell::new(vec![(1u32, String::from("a")), (2, String::from("b"))]);
+
+        let bytes = postcard::to_allocvec(&cell).unwrap();
+        let copy: AtomicRefCell<Vec<(u32, String)>> = postcard::from_bytes(&bytes).unwrap();
+        assert_eq!(copy.state(), BorrowState::Unborrowed);
+        assert_eq!(copy.into_inner(), *cell.borrow());
+
+        let guard = cell.borrow_mut();
+        assert!(postcard::to_allocvec(&cell).is_err());
+        drop(guard);
+        assert_eq!(cell.state(), BorrowState::Unborrowed);
+    }
+
+    #[test]
+    #[cfg(not(feature = "single-threaded"))]
+    fn borrow_failed() {
+        let borrow = AtomicBorrow::new();
+
+        assert!(borrow.borrow_mut());
+        assert!(!borrow.borrow());
+        assert_eq!(
+            borrow.borrow.load(Ordering::Relaxed),
+            AtomicBorrow::UNIQUE_MASK
+        );
+        borrow.release_mut();
+
+        borrow
+            .borrow
+            .store(AtomicBorrow::SHARED_MASK - 1, Ordering::Relaxed);
+        assert!(borrow.borrow());
+        let result = std::panic::catch_unwind(|| borrow.borrow());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn borrow_child_mut() {
+        let parent = AtomicBorrow::new();
+        let children = [AtomicBorrow::new(), AtomicBorrow::new()];
+        let mut fields = [1, 2];
+
+        let mut a =
+            unsafe { AtomicBorrow::borrow_child_mut(&parent, &children[0], &mut fields[0]) }
+                .unwrap();
+        let b = unsafe { AtomicBorrow::borrow_child_mut(&parent, &children[1], &mut fields[1]) }
+            .unwrap();
+        *a += *b;
+        assert_eq!(parent.shared_count(), 2);
+
+        // the child is taken, so the parent is released again
+        let c = unsafe { AtomicBorrow::borrow_child_mut(&parent, &children[0], &mut fields[0]) };
+        assert!(c.is_none());
+        assert_eq!(parent.shared_count(), 2);
+        assert!(!parent.borrow_mut());
+
+        drop(a);
+        assert!(!children[0].is_borrowed());
+        assert_eq!(parent.shared_count(), 1);
+        drop(b);
+        assert!(!parent.is_borrowed());
+        assert_eq!(fields, [3, 2]);
+
+        // the whole structure is uniquely borrowed, so no field can be
+        assert!(parent.borrow_mut());
+        let d = unsafe { AtomicBorrow::borrow_child_mut(&parent, &children[1], &mut fields[1]) };
+        assert!(d.is_none());
+        assert!(!children[1].is_borrowed());
+        parent.release_mut();
+    }
+
+    #[test]
+    #[cfg(not(feature = "single-threaded"))]
+    fn slot_pool() {
+        let pool = SlotPool::<String, 8>::new();
+
+        let a = pool.insert(String::from("a")).unwrap();
+        let guard = pool.get(a).unwrap();
+        assert_eq!(*guard, "a");
+        drop(guard);
+        assert_eq!(pool.remove(a).as_deref(), Some("a"));
+        assert!(pool.get(a).is_none());
+        assert!(pool.remove(a).is_none());
+
+        // the slot is reused, but the stale handle stays invalid
+        let b = pool.insert(String::from("b")).unwrap();
+        assert_eq!(a.index(), b.index());
+        assert_eq!(a.generation() + 1, b.generation());
+        assert!(!pool.contains(a));
+        assert!(pool.contains(b));
+        assert!(pool.get(a).is_none());
+        assert!(pool.remove(a).is_none());
+        assert_eq!(*pool.get(b).unwrap(), "b");
+
+        std::thread::scope(|s| {
+            for i in 0..4 {
+                let pool = &pool;
+                s.spawn(move || {
+                    for j in 0..1000 {
+                        let value = format!("{}-{}", i, j);
+
+                        let slot = loop {
+                            if let Some(slot) = pool.insert(value.clone()) {
+                                break slot;
+                            }
+                        };
+
+                        assert_eq!(*pool.get(slot).unwrap(), value);
+                        assert_eq!(pool.remove(slot), Some(value));
+                        assert!(pool.get(slot).is_none());
+                    }
+                });
+            }
+        });
+
+        assert_eq!(pool.len(), 1);
+        for _ in 0..7 {
+            assert!(pool.insert(String::new()).is_some());
+        }
+        assert!(pool.insert(String::new()).is_none());
+    }
+
+    #[test]
+    fn seqcst() {
+        let borrow = AtomicBorrow::new();
+
+        assert!(borrow.borrow_seqcst());
+        assert!(borrow.borrow());
+        assert!(!borrow.borrow_mut_seqcst());
+        borrow.release_seqcst();
+        borrow.release();
+
+        assert!(borrow.borrow_mut_seqcst());
+        #[cfg(feature = "debug-owner")]
+        assert!(borrow.is_held_by_current_thread());
+        assert!(!borrow.borrow_seqcst());
+        assert!(!borrow.borrow_mut());
+        assert_eq!(borrow.shared_count(), 0);
+        borrow.release_mut_seqcst();
+        #[cfg(feature = "debug-owner")]
+        assert!(!borrow.is_held_by_current_thread());
+        assert!(!borrow.is_borrowed());
+    }
+
+    #[test]
+    #[cfg(feature = "test-failpoints")]
+    fn fail_next_borrow() {
+        let borrow = AtomicBorrow::new();
+        let other = AtomicBorrow::new();
+
+        borrow.fail_next_borrow();
+        assert!(other.borrow_mut());
+        assert!(!borrow.borrow());
+        assert!(!borrow.is_borrowed());
+        assert!(borrow.borrow());
+        borrow.release();
+        other.release_mut();
+
+        borrow.fail_nth_borrow(3);
+        assert!(borrow.borrow_mut());
+        borrow.release_mut();
+        assert!(borrow.borrow());
+        assert!(!borrow.borrow());
+        assert!(borrow.borrow());
+        borrow.release();
+        borrow.release();
+
+        borrow.fail_nth_borrow(1);
+        borrow.fail_nth_borrow(0);
+        assert!(borrow.borrow_mut());
+        borrow.release_mut();
+    }
+
+    #[test]
+    #[cfg(feature = "test-failpoints")]
+    fn fail_next_try_borrow() {
+        let cell = AtomicRefCell::new(5);
+
+        // SAFETY: only used to arm failpoints, nothing is released through it
+        let borrow = unsafe { cell.get_borrow() };
+
+        borrow.fail_next_borrow();
+        assert_eq!(cell.try_borrow().err(), Some(BorrowError::UniquelyBorrowed));
+        assert_eq!(cell.state(), BorrowState::Unborrowed);
+        assert_eq!(*cell.try_borrow().unwrap(), 5);
+
+        borrow.fail_next_borrow();
+        assert_eq!(
+            cell.try_borrow_mut().err(),
+            Some(BorrowMutError::UniquelyBorrowed)
+        );
+        assert_eq!(cell.state(), BorrowState::Unborrowed);
+        *cell.try_borrow_mut().unwrap() = 6;
+
+        assert_eq!(cell.into_inner(), 6);
+    }
+
+    #[test]
+    #[cfg(feature = "test-failpoints")]
+    fn fail_next_borrow_send_shared_clone() {
+        let borrow = AtomicBorrow::new();
+        let guard = SendSharedGuard::try_new(&5, &borrow).unwrap();
+
+        // cloning holds a reference already, so it doesn't count as a borrow attempt
+        borrow.fail_next_borrow();
+        let clone = guard.clone();
+        assert_eq!(borrow.shared_count(), 2);
+        assert!(!borrow.borrow());
+
+        drop((guard, clone));
+        assert!(!borrow.is_borrowed());
+    }
+
+    #[test]
+    #[cfg(feature = "tracing")]
+    fn borrow_logged() {
+        use std::sync::Mutex;
+        use tracing::{
+            field::{Field, Visit},
+            span, Event, Metadata, Subscriber,
+        };
+
+        #[derive(Default)]
+        struct Capture(Mutex<Vec<(String, u64)>>);
+
+        #[derive(Default)]
+        struct Fields {
+            message: String,
+            line: u64,
+        }
+
+        impl Visit for Fields {
+            fn record_u64(&mut self, field: &Field, value: u64) {
+                if field.name() == "line" {
+                    self.line = value;
+                }
+            }
+
+            fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+                if field.name() == "message" {
+                    self.message = format!("{:?}", value);
+                }
+            }
+        }
+
+        impl Subscriber for &'static Capture {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+                span::Id::from_u64(1)
+            }
+
+            fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+            fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+            fn event(&self, event: &Event<'_>) {
+                let mut fields = Fields::default();
+                event.record(&mut fields);
+                self.0.lock().unwrap().push((fields.message, fields.line));
+            }
+
+            fn enter(&self, _: &span::Id) {}
+
+            fn exit(&self, _: &span::Id) {}
+        }
+
+        let capture: &'static Capture = Box::leak(Box::default());
+        let cell = AtomicRefCell::new(1);
+
+        tracing::subscriber::with_default(capture, || {
+            let guard = cell.borrow_logged().unwrap();
+            let line = line!() - 1;
+            assert_eq!(guard.location().line(), line);
+            assert!(cell.borrow_mut_logged().is_none());
+            drop(guard);
+
+            let mut guard = cell.borrow_mut_logged().unwrap();
+            *guard += 1;
+            drop(guard);
+
+            let events = capture.0.lock().unwrap();
+            assert_eq!(
+                *events,
+                [
+                    (String::from("borrow acquired"), line as u64),
+                    (String::from("borrow released"), line as u64),
+                    (String::from("borrow acquired"), line as u64 + 6),
+                    (String::from("borrow released"), line as u64 + 6),
+                ]
+            );
+        });
+
+        assert_eq!(cell.state(), BorrowState::Unborrowed);
+    }
+
+    #[test]
+    fn clone_unborrowed() {
+        #[derive(Clone)]
+        struct Slot {
+            borrow: AtomicBorrow,
+            value: i32,
+        }
+
+        let slot = Slot {
+            borrow: AtomicBorrow::new(),
+            value: 1,
+        };
+        assert!(slot.borrow.borrow_mut());
+
+        let clone = slot.clone();
+        assert_eq!(clone.value, 1);
+        assert!(!clone.borrow.is_borrowed());
+        assert!(slot.borrow.is_borrowed());
+        slot.borrow.release_mut();
+    }
+
+    #[test]
+    fn guard_index() {
+        fn first<G: Index<usize, Output = i32>>(guard: &G) -> i32 {
+            guard[0]
+        }
+
+        let cell = AtomicRefCell::new(vec![1, 2, 3]);
+
+        let guard = cell.borrow();
+        assert_eq!(guard[1], 2);
+        assert_eq!(first(&guard), 1);
+        assert_eq!(guard[1..], [2, 3]);
+        drop(guard);
+
+        let mut guard = cell.borrow_mut();
+        guard[2] = 4;
+        guard[..2].swap(0, 1);
+        assert_eq!(first(&guard), 2);
+        drop(guard);
+
+        assert_eq!(cell.into_inner(), [2, 1, 4]);
+    }
+
+    #[test]
+    fn guard_cmp() {
+        let a = AtomicRefCell::new(1);
+        let b = AtomicRefCell::new(2);
+
+        let (x, y) = (a.borrow(), b.borrow());
+        assert_eq!(x, 1);
+        assert_ne!(x, y);
+        assert!(x < y);
+        assert!(y > 1);
+        assert_eq!(x, a.borrow());
+        drop((x, y));
+
+        let (x, y) = (a.borrow_mut(), b.borrow_mut());
+        assert_eq!(x, 1);
+        assert!(x < y);
+        assert!(x <= 1);
+        assert_eq!(format!("{:?}", y), "2");
+    }
+
+    #[test]
+    fn cell_aliasing() {
+        let cell = AtomicRefCell::new([0u32; 4]);
+
+        let a = cell.borrow();
+        let b = cell.try_borrow().unwrap();
+        assert_eq!(a[0] + b[3], 0);
+        assert!(cell.try_borrow_mut().is_err());
+        drop((a, b));
+
+        let mut guard = cell.borrow_mut();
+        guard[1] = 1;
+        let inner = &mut guard[2];
+        *inner = 2;
+        drop(guard);
+
+        let a = cell.borrow();
+        assert_eq!(*a, [0, 1, 2, 0]);
+        drop(a);
+
+        // writes through the raw pointer are fine while nothing is borrowed
+        unsafe { (*cell.as_ptr())[3] = 3 };
+        assert_eq!(*cell.borrow(), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn guard_drain() {
+        let cell = AtomicRefCell::new(vec![1, 2, 3, 4]);
+
+        let mut drain = cell.borrow_mut().into_drain();
+        assert_eq!(drain.len(), 4);
+        assert_eq!(drain.next(), Some(1));
+        assert_ne!(cell.state(), BorrowState::Unborrowed);
+        assert_eq!(drain.collect::<Vec<_>>(), [2, 3, 4]);
+        assert_eq!(cell.state(), BorrowState::Unborrowed);
+
+        cell.borrow_mut().extend([5, 6]);
+        let mut drain = cell.borrow_mut().into_drain();
+        assert_eq!(drain.next(), Some(5));
+        drop(drain);
+        assert_eq!(cell.state(), BorrowState::Unborrowed);
+        assert!(cell.borrow().is_empty());
+    }
+
+    #[test]
+    fn snapshot_arc() {
+        let cell = AtomicRefCell::new(vec![1, 2]);
+
+        let guard = cell.borrow();
+        let snapshot = SharedGuard::snapshot_arc(&guard);
+        assert_eq!(*snapshot, *guard);
+        drop(guard);
+
+        cell.borrow_mut().push(3);
+        assert_eq!(*snapshot, [1, 2]);
+        assert_eq!(std::sync::Arc::strong_count(&snapshot), 1);
+        assert_eq!(cell.state(), BorrowState::Unborrowed);
+    }
+
+    #[test]
+    fn guard_hash() {
+        use std::{collections::hash_map::RandomState, hash::BuildHasher};
+
+        let cell = AtomicRefCell::new(String::from("foo"));
+        let state = RandomState::new();
+
+        assert_eq!(state.hash_one(cell.borrow()), state.hash_one("foo"));
+        assert_eq!(state.hash_one(cell.borrow_mut()), state.hash_one("foo"));
+    }
+
+    #[test]
+    #[cfg(not(feature = "single-threaded"))]
+    fn mailbox() {
+        let mailbox = Mailbox::new();
+
+        assert_eq!(mailbox.recv(), None);
+        assert_eq!(mailbox.send(1), Ok(()));
+        assert_eq!(mailbox.send(2), Err(2));
+        assert_eq!(mailbox.recv(), Some(1));
+        assert_eq!(mailbox.recv(), None);
+
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                for i in 0..1000 {
+                    let mut value = i;
+                    while let Err(v) = mailbox.send(value) {
+                        value = v;
+                        std::thread::yield_now();
+                    }
+                }
+            });
+
+            for i in 0..1000 {
+                let value = loop {
+                    if let Some(value) = mailbox.recv() {
+                        break value;
+                    }
+                    std::thread::yield_now();
+                };
+
+                assert_eq!(value, i);
+            }
+        });
+
+        assert_eq!(mailbox.into_inner(), None);
+    }
+
+    #[test]
+    fn release_now() {
+        let cell = AtomicRefCell::new(1);
+
+        let guard = cell.borrow();
+        guard.release_now();
+        assert_eq!(cell.state(), BorrowState::Unborrowed);
+
+        let guard = cell.borrow_mut();
+        guard.release_now();
+        assert_eq!(cell.state(), BorrowState::Unborrowed);
+
+        let guard = cell.borrow_mut();
+        let old = guard.release_now_with(|x| std::mem::replace(x, 2));
+        assert_eq!(old, 1);
+        assert_eq!(cell.state(), BorrowState::Unborrowed);
+        assert_eq!(cell.into_inner(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "debug-backtrace")]
+    fn acquire_backtrace() {
+        #[inline(never)]
+        fn acquire_for_backtrace(borrow: &AtomicBorrow) {
+            assert!(borrow.borrow_mut());
+        }
+
+        let borrow = AtomicBorrow::new();
+        assert!(borrow.acquire_backtrace().is_none());
+
+        acquire_for_backtrace(&borrow);
+        let backtrace = borrow.acquire_backtrace().unwrap();
+        assert!(backtrace.contains("acquire_for_backtrace"));
+
+        borrow.release_mut();
+        assert!(borrow.acquire_backtrace().is_none());
+
+        assert!(borrow.checked_borrow_mut().is_ok());
+        assert!(borrow.acquire_backtrace().is_some());
+        assert!(borrow.checked_release_mut().is_ok());
+        assert!(borrow.acquire_backtrace().is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "debug-owner")]
+    #[cfg(not(feature = "single-threaded"))]
+    fn is_held_by_current_thread() {
+        let borrow = AtomicBorrow::new();
+        assert!(!borrow.is_held_by_current_thread());
+
+        assert!(borrow.borrow_mut());
+        assert!(borrow.is_held_by_current_thread());
+        std::thread::scope(|s| {
+            s.spawn(|| assert!(!borrow.is_held_by_current_thread()));
+        });
+        borrow.release_mut();
+        assert!(!borrow.is_held_by_current_thread());
+
+        std::thread::scope(|s| {
+            s.spawn(|| assert!(borrow.checked_borrow_mut().is_ok()));
+        });
+        assert!(!borrow.is_held_by_current_thread());
+        assert!(borrow.checked_release_mut().is_ok());
+        assert!(!borrow.is_held_by_current_thread());
+    }
+
+    #[test]
+    fn fmt_write() {
+        use std::fmt::Write;
+
+        let cell = AtomicRefCell::new(String::new());
+
+        let (name, n) = ("a", 1);
+        let mut guard = cell.borrow_mut();
+        write!(guard, "{}-{:02}", name, n).unwrap();
+        guard.write_char('!').unwrap();
+        drop(guard);
+
+        assert_eq!(cell.into_inner(), "a-01!");
+    }
+
+    #[test]
+    fn as_deref() {
+        let string = AtomicRefCell::new(String::from("abc"));
+        let bytes = AtomicRefCell::new(vec![1u8, 2]);
+
+        let guard = string.borrow();
+        let str: &str = SharedGuard::as_deref(&guard);
+        assert_eq!(str, "abc");
+        drop(guard);
+
+        let mut guard = string.borrow_mut();
+        UniqueGuard::as_deref_mut(&mut guard).make_ascii_uppercase();
+        assert_eq!(UniqueGuard::as_deref(&guard), "ABC");
+        drop(guard);
+
+        let guard = bytes.borrow();
+        let slice: &[u8] = SharedGuard::as_deref(&guard);
+        assert_eq!(slice, [1, 2]);
+    }
+
+    #[test]
+    fn compact_guard() {
+        assert_eq!(
+            std::mem::size_of::<CompactGuard<'_, u64>>(),
+            std::mem::size_of::<*const u64>()
+        );
+        assert_eq!(
+            std::mem::size_of::<Option<CompactGuardMut<'_, u8>>>(),
+            std::mem::size_of::<*const u8>()
+        );
+
+        let values = [Colocated::new(1u8), Colocated::new(2u8)];
+
+        let a = values[0].try_borrow().unwrap();
+        let mut b = values[1].try_borrow_mut().unwrap();
+        *b += *a;
+        // SAFETY: the borrows are only compared
+        unsafe {
+            assert!(std::ptr::eq(a.get_borrow(), values[0].get_borrow()));
+            assert!(std::ptr::eq(b.get_borrow(), values[1].get_borrow()));
+        }
+        assert!(values[0].try_borrow_mut().is_none());
+        assert!(values[1].try_borrow().is_none());
+
+        drop(a);
+        assert_eq!(values[0].state(), BorrowState::Unborrowed);
+        assert_eq!(values[1].state(), BorrowState::Unique);
+        drop(b);
+        assert_eq!(values[1].state(), BorrowState::Unborrowed);
+
+        let [a, b] = values;
+        assert_eq!((a.into_inner(), b.into_inner()), (1, 3));
+    }
+
+    #[test]
+    #[cfg_attr(feature = "single-threaded", allow(clippy::arc_with_non_send_sync))]
+    fn borrow_arc() {
+        use std::sync::Arc;
+
+        let arc = Arc::new(AtomicRefCell::new(String::from("a")));
+        let weak = Arc::downgrade(&arc);
+
+        let mut guard = AtomicRefCell::borrow_mut_arc(&arc).unwrap();
+        guard.push('b');
+        assert!(AtomicRefCell::borrow_arc(&arc).is_none());
+        drop(guard);
+
+        let guard = AtomicRefCell::borrow_arc(&arc).unwrap();
+        drop(arc);
+        assert!(weak.upgrade().is_some());
+        assert_eq!(*guard, "ab");
+
+        drop(guard);
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    #[cfg(not(feature = "single-threaded"))]
+    #[cfg(feature = "test-failpoints")]
+    fn borrow_overflow() {
+        use std::{panic::Location, sync::Mutex};
+
+        static LOCATIONS: Mutex<Vec<(String, u32)>> = Mutex::new(Vec::new());
+
+        let hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(|info| {
+            if info.payload().downcast_ref::<&str>() == Some(&"borrow counter overflowed") {
+                let location = info.location().unwrap();
+                let location = (location.file().to_string(), location.line());
+                LOCATIONS.lock().unwrap().push(location);
+            }
+        }));
+
+        let borrow = AtomicBorrow::new();
+        unsafe { borrow.force_shared_count(AtomicBorrow::SHARED_MASK - 1) };
+        assert!(borrow.borrow());
+        assert_eq!(borrow.shared_count(), AtomicBorrow::SHARED_MASK);
+
+        let location = Location::caller();
+        let result = std::panic::catch_unwind(|| borrow.borrow());
+        let line = line!() - 1;
+        assert!(result.is_err());
+
+        std::panic::set_hook(hook);
+        let expected = (location.file().to_string(), line);
+        assert!(LOCATIONS.lock().unwrap().contains(&expected));
+
+        // the counter is unchanged, so releasing recovers from the overflow
+        assert_eq!(borrow.shared_count(), AtomicBorrow::SHARED_MASK);
+        assert!(!borrow.borrow_mut());
+        borrow.release();
+        assert!(borrow.borrow());
+        unsafe { borrow.force_shared_count(0) };
+        assert!(borrow.borrow_mut());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn leak_hook() {
+        use std::{sync::Mutex, thread::ThreadId};
+
+        static LEAKED: Mutex<Vec<(ThreadId, usize)>> = Mutex::new(Vec::new());
+
+        // other tests may leak concurrently, so only look at this thread
+        let leaked = || {
+            let current = std::thread::current().id();
+            let leaked = LEAKED.lock().unwrap();
+            let states = leaked.iter().filter(|(id, _)| *id == current);
+            states.map(|(_, state)| *state).collect::<Vec<_>>()
+        };
+
+        AtomicBorrow::set_leak_hook(|borrow| {
+            let state = unsafe { *borrow.as_ptr() };
+            let current = std::thread::current().id();
+            LEAKED.lock().unwrap().push((current, state));
+        });
+
+        let cell = AtomicRefCell::new(0);
+        std::mem::forget(cell.borrow());
+        drop(cell);
+
+        let borrow = AtomicBorrow::new();
+        assert!(borrow.borrow_mut());
+        borrow.release_mut();
+        drop(borrow);
+
+        let borrow = AtomicBorrow::new();
+        assert!(borrow.borrow_mut());
+        drop(borrow);
+
+        AtomicBorrow::clear_leak_hook();
+        assert_eq!(leaked(), [1, AtomicBorrow::UNIQUE_MASK]);
+    }
+
+    #[test]
+    fn borrow_cas() {
+        let borrow = AtomicBorrow::new();
+
+        assert!(borrow.borrow_cas());
+        assert!(borrow.borrow_cas());
+        assert_eq!(borrow.shared_count(), 2);
+        assert!(!borrow.borrow_mut());
+        borrow.release();
+        borrow.release();
+
+        assert!(borrow.borrow_mut());
+        let raw = unsafe { *borrow.as_ptr() };
+        assert!(!borrow.borrow_cas());
+        assert_eq!(unsafe { *borrow.as_ptr() }, raw);
+        borrow.release_mut();
+
+        #[cfg(feature = "test-failpoints")]
+        {
+            unsafe { borrow.force_shared_count(AtomicBorrow::SHARED_MASK) };
+            let borrow_cas = std::panic::AssertUnwindSafe(|| borrow.borrow_cas());
+            let result = std::panic::catch_unwind(borrow_cas);
+            assert!(result.is_err());
+            assert_eq!(borrow.shared_count(), AtomicBorrow::SHARED_MASK);
+            unsafe { borrow.force_shared_count(0) };
+        }
+    }
+
+    #[test]
+    fn borrow_nonblocking() {
+        let borrow = AtomicBorrow::new();
+
+        assert!(borrow.borrow_nonblocking().is_ok());
+        let err = borrow.borrow_mut_nonblocking().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+        borrow.release();
+
+        assert!(borrow.borrow_mut_nonblocking().is_ok());
+        let err = borrow.borrow_nonblocking().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+        borrow.release_mut();
+        assert!(!borrow.is_borrowed());
+    }
+
+    #[test]
+    fn migrate_state() {
+        let from = AtomicBorrow::new();
+        let to = AtomicBorrow::new();
+
+        assert_eq!(AtomicBorrow::migrate_state(&from, &to), Ok(()));
+        assert!(!to.is_borrowed());
+
+        assert!(from.borrow());
+        assert!(from.borrow());
+        assert_eq!(AtomicBorrow::migrate_state(&from, &to), Ok(()));
+        assert_eq!(to.shared_count(), 2);
+
+        // `to` is borrowed now
+        assert_eq!(AtomicBorrow::migrate_state(&from, &to), Err(()));
+        to.release();
+        to.release();
+        from.release();
+        from.release();
+
+        assert!(from.borrow_mut());
+        assert_eq!(AtomicBorrow::migrate_state(&from, &to), Ok(()));
+        assert!(!to.borrow());
+        to.release_mut();
+        from.release_mut();
+    }
+
+    #[test]
+    fn rebind_guard() {
+        let old = (AtomicBorrow::new(), 1);
+        let new = (AtomicBorrow::new(), 2);
+
+        let mut guard = unsafe { SharedGuard::try_new(&old.1, &old.0) }.unwrap();
+        assert!(unsafe { SharedGuard::rebind(&mut guard, &new.0, &new.1) });
+        assert_eq!(*guard, 2);
+        assert!(!old.0.is_borrowed());
+        assert_eq!(new.0.shared_count(), 1);
+        assert!(!new.0.borrow_mut());
+
+        // can't move onto a uniquely borrowed location
+        assert!(old.0.borrow_mut());
+        assert!(!unsafe { SharedGuard::rebind(&mut guard, &old.0, &old.1) });
+        assert_eq!(*guard, 2);
+        assert_eq!(new.0.shared_count(), 1);
+        old.0.release_mut();
+
+        drop(guard);
+        assert!(!new.0.is_borrowed());
+    }
+
+    #[test]
+    fn cloned_copied() {
+        let string = AtomicRefCell::new(String::from("a"));
+        let int = AtomicRefCell::new(1);
+
+        let guard = string.borrow();
+        assert_eq!(SharedGuard::cloned(&guard), "a");
+        drop(guard);
+
+        let mut guard = string.borrow_mut();
+        guard.push('b');
+        assert_eq!(UniqueGuard::cloned(&guard), "ab");
+        drop(guard);
+
+        assert_eq!(SharedGuard::copied(&int.borrow()), 1);
+        let mut guard = int.borrow_mut();
+        *guard = 2;
+        assert_eq!(UniqueGuard::copied(&guard), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "parking")]
+    #[cfg(not(feature = "single-threaded"))]
+    fn smart_borrow_mut() {
+        use std::time::Duration;
+
+        let borrow = SmartBorrow::new();
+
+        // uncontended acquisitions aren't probed
+        borrow.smart_borrow_mut();
+        borrow.release_mut();
+        assert_eq!(borrow.strategy(), None);
+
+        for _ in 0..SmartBorrow::PROBES + 1 {
+            assert!(borrow.borrow_mut());
+
+            std::thread::scope(|s| {
+                s.spawn(|| {
+                    borrow.smart_borrow_mut();
+                    borrow.release_mut();
+                });
+
+                std::thread::sleep(Duration::from_millis(1));
+                borrow.release_mut();
+            });
+        }
+
+        assert!(borrow.strategy().is_some());
+        assert!(!borrow.is_borrowed());
+    }
+
+    #[test]
+    #[cfg(feature = "usdt")]
+    #[cfg(not(feature = "single-threaded"))]
+    fn usdt_probe() {
+        let borrow = AtomicBorrow::new();
+
+        // exhaust the spins so the probe fires
+        assert!(borrow.borrow_mut());
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                borrow.spin_borrow_mut();
+                borrow.release_mut();
+            });
+
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            borrow.release_mut();
+        });
+        assert!(!borrow.is_borrowed());
+
+        // the probe is described in the `.note.stapsdt` section of the binary
+        #[cfg(target_os = "linux")]
+        {
+            let exe = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+            let contains = |needle: &[u8]| exe.windows(needle.len()).any(|w| w == needle);
+            assert!(contains(b"stapsdt\0"));
+            assert!(contains(b"atomic_borrow\0contended\0"));
+        }
+    }
+
+    #[test]
+    fn transaction() {
+        let borrows = [
+            AtomicBorrow::new(),
+            AtomicBorrow::new(),
+            AtomicBorrow::new(),
+        ];
+        let [a, b, c] = &borrows;
+
+        // one contended resource rolls back the others
+        assert!(b.borrow_mut());
+        assert!(AtomicBorrow::transaction(&[a, b, c]).is_none());
+        assert!(!a.is_borrowed());
+        assert!(!c.is_borrowed());
+        b.release_mut();
+
+        let transaction = AtomicBorrow::transaction(&[c, a, b, a]).unwrap();
+        assert_eq!(transaction.borrows().len(), 3);
+        assert!(transaction.contains(b));
+        assert!(borrows.iter().all(|borrow| !borrow.borrow()));
+        assert!(AtomicBorrow::transaction(&[c]).is_none());
+
+        transaction.commit();
+        assert!(borrows.iter().all(|borrow| !borrow.is_borrowed()));
+    }
+
+    #[test]
+    #[cfg(not(feature = "single-threaded"))]
+    fn reentrant_borrow() {
+        let borrow = ReentrantBorrow::new();
+
+        let outer = borrow.reentrant_borrow_mut().unwrap();
+        let inner = borrow.reentrant_borrow_mut().unwrap();
+        assert_eq!(borrow.depth(), 2);
+        assert!(!borrow.borrow());
+
+        std::thread::scope(|s| {
+            s.spawn(|| assert!(borrow.reentrant_borrow_mut().is_none()));
+        });
+
+        drop(inner);
+        assert_eq!(borrow.depth(), 1);
+        assert!(borrow.is_borrowed());
+
+        std::thread::scope(|s| {
+            s.spawn(|| assert!(borrow.reentrant_borrow_mut().is_none()));
+        });
+
+        drop(outer);
+        assert_eq!(borrow.depth(), 0);
+        assert!(!borrow.is_borrowed());
+
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                let guard = borrow.reentrant_borrow_mut().unwrap();
+                assert_eq!(guard.get_borrow().depth(), 1);
+            });
+        });
+        assert!(!borrow.is_borrowed());
+    }
+
+    #[test]
+    fn padded_array() {
+        type Array = PaddedAtomicBorrowArray<4>;
+        assert_eq!(std::mem::size_of::<Array>(), 4 * Array::PADDING);
+
+        let array = Array::new();
+        let (a, b) = (array.get(0).unwrap(), array.get(1).unwrap());
+        assert!(std::ptr::eq(a, &array[0]));
+        assert_eq!(
+            b as *const _ as usize - a as *const _ as usize,
+            Array::PADDING
+        );
+        assert!(array.get(4).is_none());
+
+        assert!(array.try_borrow_mut(0));
+        assert!(array.try_borrow(1));
+        assert!(!array.try_borrow(0));
+        assert!(!array.try_borrow_mut(1));
+        assert_eq!(
+            array.iter().filter(|borrow| borrow.is_borrowed()).count(),
+            2
+        );
+
+        array[0].release_mut();
+        array[1].release();
+        assert!(array.iter().all(|borrow| !borrow.is_borrowed()));
+    }
+
+    #[test]
+    #[cfg(not(feature = "single-threaded"))]
+    fn borrow_policy() {
+        let policies = [
+            BorrowPolicy::new().spin_count(usize::MAX).backoff(true),
+            BorrowPolicy::new().spin_count(0),
+            BorrowPolicy::new().jitter(true).backoff(true),
+            BorrowPolicy::new().fair(true),
+        ];
+
+        for policy in &policies {
+            let borrow = AtomicBorrow::new();
+            borrow.borrow_with_policy(policy);
+            borrow.borrow_with_policy(policy);
+            borrow.release();
+            borrow.release();
+
+            // contended, the holder releases while the waiter waits
+            assert!(borrow.borrow_mut());
+            std::thread::scope(|s| {
+                s.spawn(|| {
+                    borrow.borrow_mut_with_policy(policy);
+                    borrow.release_mut();
+                });
+
+                std::thread::sleep(std::time::Duration::from_millis(1));
+                borrow.release_mut();
+            });
+            assert!(!borrow.is_borrowed());
+        }
+
+        // fair waiters acquire in the order they queued up
+        let borrow = AtomicBorrow::new();
+        let order = std::sync::Mutex::new(Vec::new());
+        let fair = BorrowPolicy::new().fair(true).spin_count(0);
+
+        assert!(borrow.borrow_mut());
+        std::thread::scope(|s| {
+            for i in 0..4 {
+                let (borrow, order) = (&borrow, &order);
+                s.spawn(move || {
+                    borrow.borrow_mut_with_policy(&fair);
+                    order.lock().unwrap().push(i);
+                    borrow.release_mut();
+                });
+
+                while borrow.fair_waiters() <= i {
+                    std::thread::yield_now();
+                }
+            }
+
+            borrow.release_mut();
+        });
+
+        assert_eq!(*order.lock().unwrap(), [0, 1, 2, 3]);
+        assert_eq!(borrow.fair_waiters(), 0);
+    }
+
+    #[test]
+    fn peak_borrow() {
+        let borrow = PeakBorrow::new();
+        assert_eq!(borrow.peak_shared(), 0);
+
+        assert!(borrow.borrow());
+        assert!(borrow.borrow());
+        borrow.release();
+        assert!(borrow.borrow());
+        assert!(borrow.borrow());
+        assert_eq!(borrow.peak_shared(), 3);
+
+        for _ in 0..3 {
+            borrow.release();
+        }
+        assert!(borrow.borrow());
+        assert_eq!(borrow.peak_shared(), 3);
+        borrow.release();
+
+        assert!(borrow.borrow_mut());
+        assert!(!borrow.borrow());
+        assert_eq!(borrow.reset_peak(), 3);
+        borrow.release_mut();
+
+        assert!(borrow.borrow());
+        assert_eq!(borrow.peak_shared(), 1);
+        assert_eq!(borrow.acquisitions(), 6);
+        borrow.release();
+
+        borrow.reset_metrics();
+        assert_eq!((borrow.peak_shared(), borrow.acquisitions()), (0, 0));
+        assert!(borrow.borrow());
+        assert!(borrow.borrow());
+        assert_eq!((borrow.peak_shared(), borrow.acquisitions()), (2, 2));
+    }
+
+    #[test]
+    fn instrumented_borrow() {
+        struct MockRecorder(std::sync::Mutex<Vec<&'static str>>);
+
+        impl BorrowRecorder for MockRecorder {
+            fn borrowed(&self) {
+                self.0.lock().unwrap().push("borrowed");
+            }
+
+            fn borrowed_mut(&self) {
+                self.0.lock().unwrap().push("borrowed_mut");
+            }
+
+            fn released(&self) {
+                self.0.lock().unwrap().push("released");
+            }
+
+            fn released_mut(&self) {
+                self.0.lock().unwrap().push("released_mut");
+            }
+
+            fn contended(&self, unique: bool) {
+                let event = if unique { "contended_mut" } else { "contended" };
+                self.0.lock().unwrap().push(event);
+            }
+        }
+
+        static RECORDER: MockRecorder = MockRecorder(std::sync::Mutex::new(Vec::new()));
+
+        let borrow = InstrumentedBorrow::new(&RECORDER);
+        assert!(borrow.borrow());
+        assert!(!borrow.borrow_mut());
+        borrow.release();
+        assert!(borrow.borrow_mut());
+        assert!(!borrow.borrow());
+        borrow.release_mut();
+
+        // only operations through the wrapper are recorded
+        let inner: &AtomicBorrow = &borrow;
+        assert!(inner.borrow());
+        inner.release();
+
+        assert_eq!(
+            *RECORDER.0.lock().unwrap(),
+            [
+                "borrowed",
+                "contended_mut",
+                "released",
+                "borrowed_mut",
+                "contended",
+                "released_mut",
+            ]
+        );
+    }
+
+    #[test]
+    fn release_guard() {
+        let borrow = AtomicBorrow::new();
+
+        assert!(borrow.borrow());
+        let guard = borrow.defer_release();
+        assert!(!guard.is_unique());
+        assert_eq!(guard.get_borrow().shared_count(), 1);
+        drop(guard);
+        assert!(!borrow.is_borrowed());
+
+        assert!(borrow.borrow_mut());
+        let guard = borrow.defer_release_mut();
+        assert!(guard.is_unique());
+        let handed_off = guard.cancel();
+        assert!(!handed_off.borrow());
+        handed_off.release_mut();
+        assert!(!borrow.is_borrowed());
+    }
+
+    #[test]
+    #[cfg(not(feature = "single-threaded"))]
+    fn release_guard_send() {
+        let cell = AtomicRefCell::new(1);
+
+        let (data, guard) = UniqueGuard::into_release_guard(cell.borrow_mut());
+        let data = data as usize;
+        std::thread::scope(|s| {
+            s.spawn(move || {
+                unsafe { *(data as *mut i32) += 1 };
+                drop(guard);
+            });
+        });
+        assert_eq!(cell.state(), BorrowState::Unborrowed);
+
+        let (data, guard) = SharedGuard::into_release_guard(cell.borrow());
+        assert!(cell.try_borrow_mut().is_err());
+        let handed_off = std::thread::scope(|s| s.spawn(move || guard.cancel()).join().unwrap());
+        assert_eq!(unsafe { *data }, 2);
+        handed_off.release();
+        assert_eq!(cell.state(), BorrowState::Unborrowed);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn debug_checked_cell() {
+        let cell = DebugCheckedCell::new(vec![1, 2]);
+
+        cell.try_borrow_mut().unwrap().push(3);
+        assert_eq!(*cell.try_borrow().unwrap(), [1, 2, 3]);
+        assert_eq!(cell.state(), BorrowState::Unborrowed);
+
+        // mutate through the back door, without a unique borrow
+        unsafe { (&mut *cell.as_ptr())[0] = 4 };
+
+        let borrow = std::panic::AssertUnwindSafe(|| cell.try_borrow().map(drop));
+        let err = std::panic::catch_unwind(borrow).unwrap_err();
+        let msg = err.downcast_ref::<&str>().unwrap();
+        assert_eq!(*msg, "value was mutated without a unique borrow");
+        assert_eq!(cell.state(), BorrowState::Unborrowed);
+    }
+
+    #[test]
+    #[cfg(feature = "state-stream")]
+    fn state_stream() {
+        use std::{
+            sync::Arc,
+            task::{Context, Poll, Waker},
+        };
+
+        use futures_util::StreamExt;
+
+        let count = Arc::new(CountWaker::default());
+        let waker = Waker::from(count.clone());
+        let mut cx = Context::from_waker(&waker);
+        let wakes = || count.count();
+
+        let borrow = AtomicBorrow::new();
+        let mut stream = borrow.state_stream();
+        let mut poll = || stream.poll_next_unpin(&mut cx);
+
+        assert_eq!(poll(), Poll::Ready(Some(BorrowState::Unborrowed)));
+        assert_eq!(poll(), Poll::Pending);
+
+        assert!(borrow.borrow());
+        assert_eq!(wakes(), 1);
+        assert_eq!(poll(), Poll::Ready(Some(BorrowState::Shared(1))));
+        assert_eq!(poll(), Poll::Pending);
+
+        // only the latest state is yielded
+        assert!(borrow.borrow());
+        borrow.release();
+        borrow.release();
+        assert_eq!(wakes(), 2);
+        assert_eq!(poll(), Poll::Ready(Some(BorrowState::Unborrowed)));
+        assert_eq!(poll(), Poll::Pending);
+
+        assert!(borrow.borrow_mut());
+        assert_eq!(poll(), Poll::Ready(Some(BorrowState::Unique)));
+        assert_eq!(poll(), Poll::Pending);
+        borrow.release_mut();
+        assert_eq!(poll(), Poll::Ready(Some(BorrowState::Unborrowed)));
+        assert_eq!(wakes(), 4);
+        assert!(!borrow.has_watchers());
+
+        // a stream dropped while pending takes its waker with it
+        let mut pending = borrow.state_stream();
+        assert!(pending.poll_next_unpin(&mut cx).is_ready());
+        assert!(pending.poll_next_unpin(&mut cx).is_pending());
+        assert!(borrow.has_watchers());
+        drop(pending);
+        assert!(!borrow.has_watchers());
+
+        assert!(borrow.borrow());
+        borrow.release();
+        assert_eq!(wakes(), 4);
+    }
+
+    #[test]
+    fn low_bit_borrow() {
+        let borrow = LowBitBorrow::new();
+
+        assert!(borrow.borrow());
+        assert!(borrow.borrow());
+        assert_eq!(borrow.state(), 2 * LowBitBorrow::SHARED_ONE);
+        assert_eq!(borrow.shared_count(), 2);
+        assert!(!borrow.borrow_mut());
+        borrow.release();
+        borrow.release();
+        assert!(!borrow.is_borrowed());
+
+        assert!(borrow.borrow_mut());
+        assert_eq!(borrow.state(), LowBitBorrow::UNIQUE_BIT);
+        assert!(borrow.is_unique());
+        assert!(!borrow.borrow());
+        assert!(!borrow.borrow_mut());
+        assert_eq!(borrow.state(), LowBitBorrow::UNIQUE_BIT);
+        borrow.release_mut();
+        assert!(!borrow.is_borrowed());
+    }
+
+    #[test]
+    #[cfg(not(feature = "single-threaded"))]
+    fn low_bit_borrow_threads() {
+        let borrow = LowBitBorrow::new();
+        let value = std::sync::atomic::AtomicUsize::new(0);
+
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..1000 {
+                        while !borrow.borrow_mut() {
+                            std::thread::yield_now();
+                        }
+
+                        // not an atomic increment, so lost updates would show
+                        let v = value.load(Ordering::Relaxed);
+                        value.store(v + 1, Ordering::Relaxed);
+                        borrow.release_mut();
+
+                        if borrow.borrow() {
+                            assert!(!borrow.is_unique());
+                            borrow.release();
+                        }
+                    }
+                });
+            }
+        });
+
+        assert_eq!(value.into_inner(), 4000);
+        assert!(!borrow.is_borrowed());
+    }
+
+    #[test]
+    fn bits_required() {
+        const BITS: [u32; 6] = [
+            AtomicBorrow::bits_required(0),
+            AtomicBorrow::bits_required(1),
+            AtomicBorrow::bits_required(2),
+            AtomicBorrow::bits_required(255),
+            AtomicBorrow::bits_required(256),
+            AtomicBorrow::bits_required(AtomicBorrow::SHARED_MASK),
+        ];
+        assert_eq!(BITS, [1, 2, 3, 9, 10, usize::BITS]);
+
+        crate::assert_borrow_bits!(255, 9);
+        crate::assert_borrow_bits!(AtomicBorrow::SHARED_MASK, usize::BITS);
+    }
+
+    #[test]
+    fn borrow_state() {
+        let borrow = AtomicBorrow::new();
+        assert_eq!(borrow.state(), BorrowState::Unborrowed);
+        assert!(borrow.borrow());
+        assert!(borrow.borrow());
+        assert_eq!(borrow.state(), BorrowState::Shared(2));
+        borrow.release();
+        borrow.release();
+        assert!(borrow.borrow_mut());
+        assert_eq!(borrow.state(), BorrowState::Unique);
+    }
+
+    #[test]
+    #[cfg(feature = "registry")]
+    #[cfg(not(feature = "single-threaded"))]
+    fn named_registry() {
+        static A: AtomicBorrow = AtomicBorrow::new();
+        static B: AtomicBorrow = AtomicBorrow::new();
+
+        A.register("a");
+        B.register("b-old");
+        B.register("b");
+
+        assert!(A.borrow_mut());
+        assert!(B.borrow());
+
+        let states = AtomicBorrow::dump_all_borrow_states();
+        assert!(states.contains(&("a", BorrowState::Unique)));
+        assert!(states.contains(&("b", BorrowState::Shared(1))));
+        assert!(!states.iter().any(|(name, _)| *name == "b-old"));
+
+        A.release_mut();
+        B.release();
+    }
+
+    #[test]
+    #[cfg(feature = "registry")]
+    #[cfg(not(feature = "single-threaded"))]
+    fn named_registry_dot() {
+        static WORLD: AtomicBorrow = AtomicBorrow::new();
+        static ENTITIES: AtomicBorrow = AtomicBorrow::new();
+        static QUOTED: AtomicBorrow = AtomicBorrow::new();
+
+        WORLD.register("world");
+        ENTITIES.register_child("entities", &WORLD);
+        QUOTED.register("say \"hi\"");
+
+        assert!(WORLD.borrow());
+        assert!(ENTITIES.borrow_mut());
+
+        let dot = AtomicBorrow::dump_dot();
+        assert!(dot.starts_with("digraph borrows {\n"));
+        assert!(dot.ends_with("}\n"));
+
+        let node = |label: &str| {
+            let line = dot.lines().find(|line| line.contains(label)).unwrap();
+            line.trim().split(' ').next().unwrap().to_owned()
+        };
+
+        let world = node("[label=\"world\\nshared(1)\", fillcolor=khaki]");
+        let entities = node("[label=\"entities\\nunique\", fillcolor=salmon]");
+        node("[label=\"say \\\"hi\\\"\\nfree\", fillcolor=palegreen]");
+        assert!(dot.contains(&format!("    {} -> {};\n", world, entities)));
+
+        ENTITIES.release_mut();
+        WORLD.release();
+    }
+
+    #[test]
+    fn downgrade() {
+        let cell = AtomicRefCell::new(1);
+
+        let guard = UniqueGuard::downgrade(cell.borrow_mut());
+        assert_eq!(cell.state(), BorrowState::Shared(1));
+        assert!(cell.try_borrow_mut().is_err());
+        assert_eq!(*cell.borrow(), *guard);
+        drop(guard);
+        assert_eq!(cell.state(), BorrowState::Unborrowed);
+
+        // SAFETY: every reference acquired through `borrow` is released through it
+        let borrow = unsafe { cell.get_borrow() };
+        let read = unsafe {
+            borrow.write_then_read(
+                cell.as_ptr(),
+                |x| *x += 1,
+                |x| {
+                    // other readers are let in, but no writers
+                    assert!(!borrow.borrow_mut() && borrow.borrow());
+                    borrow.release();
+                    *x
+                },
+            )
+        };
+        assert_eq!(read, Some(2));
+        assert!(!borrow.is_borrowed());
+
+        let guard = cell.borrow();
+        assert!(unsafe { borrow.write_then_read(cell.as_ptr(), |_| {}, |_| {}) }.is_none());
+        drop(guard);
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| unsafe {
+            borrow.write_then_read(cell.as_ptr(), |_| {}, |_| panic!())
+        }));
+        assert!(result.is_err());
+        assert!(!borrow.is_borrowed());
+    }
 }
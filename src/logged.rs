@@ -0,0 +1,92 @@
+use std::{
+    ops::{Deref, DerefMut},
+    panic::Location,
+};
+
+use crate::{AtomicRefCell, SharedGuard, UniqueGuard};
+
+impl<T> AtomicRefCell<T> {
+    /// Like [`try_borrow`](Self::try_borrow), but emits `trace` events with the location
+    /// of the caller when the borrow is acquired and released.
+    #[inline]
+    #[track_caller]
+    pub fn borrow_logged(&self) -> Option<LoggedGuard<SharedGuard<'_, T>>> {
+        let guard = self.try_borrow().ok()?;
+        Some(LoggedGuard::new(guard, "shared", Location::caller()))
+    }
+
+    /// Like [`try_borrow_mut`](Self::try_borrow_mut), but emits `trace` events with the
+    /// location of the caller when the borrow is acquired and released.
+    #[inline]
+    #[track_caller]
+    pub fn borrow_mut_logged(&self) -> Option<LoggedGuard<UniqueGuard<'_, T>>> {
+        let guard = self.try_borrow_mut().ok()?;
+        Some(LoggedGuard::new(guard, "unique", Location::caller()))
+    }
+}
+
+/// A guard that emits `trace` events when acquired and released.
+///
+/// Created by [`AtomicRefCell::borrow_logged`] and [`AtomicRefCell::borrow_mut_logged`].
+pub struct LoggedGuard<G> {
+    // only `None` while dropping
+    guard: Option<G>,
+    kind: &'static str,
+    location: &'static Location<'static>,
+}
+
+impl<G> LoggedGuard<G> {
+    #[inline]
+    fn new(guard: G, kind: &'static str, location: &'static Location<'static>) -> Self {
+        tracing::trace!(
+            kind,
+            file = location.file(),
+            line = location.line(),
+            column = location.column(),
+            "borrow acquired",
+        );
+
+        Self {
+            guard: Some(guard),
+            kind,
+            location,
+        }
+    }
+
+    /// Gets the location the borrow was acquired at.
+    #[inline]
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+}
+
+impl<G: Deref> Deref for LoggedGuard<G> {
+    type Target = G::Target;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.guard.as_ref().unwrap()
+    }
+}
+
+impl<G: DerefMut> DerefMut for LoggedGuard<G> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.guard.as_mut().unwrap()
+    }
+}
+
+impl<G> Drop for LoggedGuard<G> {
+    #[inline]
+    fn drop(&mut self) {
+        drop(self.guard.take());
+
+        tracing::trace!(
+            kind = self.kind,
+            file = self.location.file(),
+            line = self.location.line(),
+            column = self.location.column(),
+            "borrow released",
+        );
+    }
+}
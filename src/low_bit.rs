@@ -0,0 +1,117 @@
+use std::sync::atomic::Ordering;
+
+use crate::AtomicUsize;
+
+/// A reference counter like [`AtomicBorrow`](crate::AtomicBorrow), but with the unique
+/// bit as the lowest bit and the shared count in the bits above it.
+///
+/// Shared references are counted in steps of [`SHARED_ONE`](Self::SHARED_ONE), so the
+/// state is odd exactly when a unique reference is held, and the count is a shift
+/// away. This suits code that packs the state with other flags, or hands it to C code
+/// expecting this layout. Otherwise it behaves the same as `AtomicBorrow`, which keeps
+/// the unique bit high so the count can be read without shifting.
+#[derive(Debug, Default)]
+pub struct LowBitBorrow {
+    borrow: AtomicUsize,
+}
+
+impl LowBitBorrow {
+    /// The unique borrow bit.
+    pub const UNIQUE_BIT: usize = 1;
+    /// The amount a single shared reference adds to the state.
+    pub const SHARED_ONE: usize = 2;
+    /// The maximum number of shared references.
+    pub const MAX_SHARED: usize = usize::MAX >> 1;
+
+    const_fn! {
+        /// Creates a new `LowBitBorrow`.
+        #[inline]
+        pub fn new() -> Self {
+            Self {
+                borrow: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    /// Returns the raw state, the shared count shifted left by one, or'd with the unique
+    /// bit.
+    #[inline]
+    pub fn state(&self) -> usize {
+        self.borrow.load(Ordering::Acquire)
+    }
+
+    /// Returns the number of shared references.
+    #[inline]
+    pub fn shared_count(&self) -> usize {
+        self.state() >> 1
+    }
+
+    /// Returns true if a unique reference is held.
+    #[inline]
+    pub fn is_unique(&self) -> bool {
+        self.state() & Self::UNIQUE_BIT != 0
+    }
+
+    /// Returns true if there are any references.
+    #[inline]
+    pub fn is_borrowed(&self) -> bool {
+        self.state() != 0
+    }
+
+    /// Tries to acquire a shared reference.
+    ///
+    /// Returns `true` if the reference was acquired.
+    ///
+    /// # Panics
+    /// If there are already [`MAX_SHARED`](Self::MAX_SHARED) shared references.
+    #[inline]
+    #[track_caller]
+    pub fn borrow(&self) -> bool {
+        let prev = self.borrow.fetch_add(Self::SHARED_ONE, Ordering::Acquire);
+
+        if prev >> 1 == Self::MAX_SHARED {
+            // undo the increment, which wrapped the count around to 0
+            self.borrow.fetch_sub(Self::SHARED_ONE, Ordering::Relaxed);
+            panic!("borrow counter overflowed");
+        }
+
+        if prev & Self::UNIQUE_BIT != 0 {
+            self.borrow.fetch_sub(Self::SHARED_ONE, Ordering::Release);
+            false
+        } else {
+            true
+        }
+    }
+
+    /// Tries to acquire a unique reference.
+    ///
+    /// Returns `true` if the reference was acquired.
+    #[inline]
+    pub fn borrow_mut(&self) -> bool {
+        self.borrow
+            .compare_exchange(0, Self::UNIQUE_BIT, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+    }
+
+    /// Releases a shared reference.
+    #[inline]
+    pub fn release(&self) {
+        let prev = self.borrow.fetch_sub(Self::SHARED_ONE, Ordering::Release);
+        debug_assert_ne!(
+            prev >> 1,
+            0,
+            "borrow counter underflow, this means you released more times than you borrowed"
+        );
+    }
+
+    /// Releases a unique reference.
+    #[inline]
+    pub fn release_mut(&self) {
+        let prev = self.borrow.fetch_and(!Self::UNIQUE_BIT, Ordering::Release);
+        debug_assert_ne!(
+            prev & Self::UNIQUE_BIT,
+            0,
+            "unique release of shared borrow"
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use crate::AtomicRefCell;
+
+/// A single slot mailbox, passing values from one sender to one receiver.
+///
+/// Both sides access the slot under a unique borrow, so neither ever blocks, an access
+/// that races with the other side simply fails and can be retried.
+#[derive(Debug, Default)]
+pub struct Mailbox<T> {
+    slot: AtomicRefCell<Option<T>>,
+}
+
+impl<T> Mailbox<T> {
+    const_fn! {
+        /// Creates a new empty `Mailbox`.
+        #[inline]
+        pub fn new() -> Self {
+            Self {
+                slot: AtomicRefCell::new(None),
+            }
+        }
+    }
+
+    /// Deposits `value` into the mailbox.
+    ///
+    /// Returns `value` back if the mailbox is full, or is being received from.
+    #[inline]
+    pub fn send(&self, value: T) -> Result<(), T> {
+        let Ok(mut slot) = self.slot.try_borrow_mut() else {
+            return Err(value);
+        };
+
+        if slot.is_some() {
+            return Err(value);
+        }
+
+        *slot = Some(value);
+        Ok(())
+    }
+
+    /// Takes the value out of the mailbox.
+    ///
+    /// Returns `None` if the mailbox is empty, or is being sent to.
+    #[inline]
+    pub fn recv(&self) -> Option<T> {
+        self.slot.try_borrow_mut().ok()?.take()
+    }
+
+    /// Consumes `self`, returning the value in the mailbox, if any.
+    #[inline]
+    pub fn into_inner(self) -> Option<T> {
+        self.slot.into_inner()
+    }
+}
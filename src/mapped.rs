@@ -0,0 +1,239 @@
+use std::{
+    mem,
+    ops::{Deref, DerefMut},
+};
+
+use crate::{AtomicBorrow, SharedGuard, UniqueGuard};
+
+impl<'a, T> SharedGuard<'a, T> {
+    /// Makes a [`MappedSharedGuard`] for a component of the borrowed data.
+    ///
+    /// This is an associated function to avoid conflicting with methods on `T`.
+    #[inline]
+    pub fn map<U: ?Sized, F>(s: Self, f: F) -> MappedSharedGuard<'a, U>
+    where
+        F: FnOnce(&T) -> &U,
+    {
+        let data = f(unsafe { &*s.data }) as *const U;
+        let borrow = s.borrow;
+        mem::forget(s);
+
+        MappedSharedGuard { data, borrow }
+    }
+
+    /// Tries to make a [`MappedSharedGuard`] for a component of the borrowed data,
+    /// returning the original guard if `f` returns `None`.
+    ///
+    /// This is an associated function to avoid conflicting with methods on `T`.
+    #[inline]
+    pub fn try_map<U: ?Sized, F>(s: Self, f: F) -> Result<MappedSharedGuard<'a, U>, Self>
+    where
+        F: FnOnce(&T) -> Option<&U>,
+    {
+        let data = match f(unsafe { &*s.data }) {
+            Some(data) => data as *const U,
+            None => return Err(s),
+        };
+        let borrow = s.borrow;
+        mem::forget(s);
+
+        Ok(MappedSharedGuard { data, borrow })
+    }
+}
+
+impl<'a, T> UniqueGuard<'a, T> {
+    /// Makes a [`MappedUniqueGuard`] for a component of the borrowed data.
+    ///
+    /// This is an associated function to avoid conflicting with methods on `T`.
+    #[inline]
+    pub fn map<U: ?Sized, F>(s: Self, f: F) -> MappedUniqueGuard<'a, U>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        let data = f(unsafe { &mut *s.data }) as *mut U;
+        let borrow = s.borrow;
+        mem::forget(s);
+
+        MappedUniqueGuard { data, borrow }
+    }
+
+    /// Tries to make a [`MappedUniqueGuard`] for a component of the borrowed data,
+    /// returning the original guard if `f` returns `None`.
+    ///
+    /// This is an associated function to avoid conflicting with methods on `T`.
+    #[inline]
+    pub fn try_map<U: ?Sized, F>(s: Self, f: F) -> Result<MappedUniqueGuard<'a, U>, Self>
+    where
+        F: FnOnce(&mut T) -> Option<&mut U>,
+    {
+        let data = match f(unsafe { &mut *s.data }) {
+            Some(data) => data as *mut U,
+            None => return Err(s),
+        };
+        let borrow = s.borrow;
+        mem::forget(s);
+
+        Ok(MappedUniqueGuard { data, borrow })
+    }
+
+    /// Projects a shared reference to a component of the borrowed data, keeping the
+    /// guard, eg. to pass a field to a read-only function and keep writing afterwards.
+    ///
+    /// This is an associated function to avoid conflicting with methods on `T`.
+    #[inline]
+    pub fn map_ref<U: ?Sized, F>(s: &Self, f: F) -> &U
+    where
+        F: FnOnce(&T) -> &U,
+    {
+        f(s)
+    }
+}
+
+/// A [`SharedGuard`] projected to a component of the borrowed data.
+pub struct MappedSharedGuard<'a, T: ?Sized> {
+    data: *const T,
+    borrow: &'a AtomicBorrow,
+}
+
+impl<'a, T: ?Sized> MappedSharedGuard<'a, T> {
+    /// Makes a new [`MappedSharedGuard`] for a component of the borrowed data.
+    ///
+    /// This is an associated function to avoid conflicting with methods on `T`.
+    #[inline]
+    pub fn map<U: ?Sized, F>(s: Self, f: F) -> MappedSharedGuard<'a, U>
+    where
+        F: FnOnce(&T) -> &U,
+    {
+        let data = f(unsafe { &*s.data }) as *const U;
+        let borrow = s.borrow;
+        mem::forget(s);
+
+        MappedSharedGuard { data, borrow }
+    }
+
+    /// Tries to make a new [`MappedSharedGuard`] for a component of the borrowed data,
+    /// returning the original guard if `f` returns `None`.
+    ///
+    /// This is an associated function to avoid conflicting with methods on `T`.
+    #[inline]
+    pub fn try_map<U: ?Sized, F>(s: Self, f: F) -> Result<MappedSharedGuard<'a, U>, Self>
+    where
+        F: FnOnce(&T) -> Option<&U>,
+    {
+        let data = match f(unsafe { &*s.data }) {
+            Some(data) => data as *const U,
+            None => return Err(s),
+        };
+        let borrow = s.borrow;
+        mem::forget(s);
+
+        Ok(MappedSharedGuard { data, borrow })
+    }
+
+    /// Gets the inner [`AtomicBorrow`].
+    ///
+    /// This is an associated function to avoid conflicting with methods on `T`.
+    ///
+    /// # Safety
+    /// The borrow guards the data, which may belong to an
+    /// [`AtomicRefCell`](crate::AtomicRefCell), so references must not be released
+    /// through it unless they were also acquired through it.
+    #[inline]
+    pub unsafe fn get_borrow(s: &Self) -> &'a AtomicBorrow {
+        s.borrow
+    }
+}
+
+impl<'a, T: ?Sized> Deref for MappedSharedGuard<'a, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.data }
+    }
+}
+
+impl<'a, T: ?Sized> Drop for MappedSharedGuard<'a, T> {
+    #[inline]
+    fn drop(&mut self) {
+        self.borrow.release();
+    }
+}
+
+/// A [`UniqueGuard`] projected to a component of the borrowed data.
+pub struct MappedUniqueGuard<'a, T: ?Sized> {
+    data: *mut T,
+    borrow: &'a AtomicBorrow,
+}
+
+impl<'a, T: ?Sized> MappedUniqueGuard<'a, T> {
+    /// Makes a new [`MappedUniqueGuard`] for a component of the borrowed data.
+    ///
+    /// This is an associated function to avoid conflicting with methods on `T`.
+    #[inline]
+    pub fn map<U: ?Sized, F>(s: Self, f: F) -> MappedUniqueGuard<'a, U>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        let data = f(unsafe { &mut *s.data }) as *mut U;
+        let borrow = s.borrow;
+        mem::forget(s);
+
+        MappedUniqueGuard { data, borrow }
+    }
+
+    /// Tries to make a new [`MappedUniqueGuard`] for a component of the borrowed data,
+    /// returning the original guard if `f` returns `None`.
+    ///
+    /// This is an associated function to avoid conflicting with methods on `T`.
+    #[inline]
+    pub fn try_map<U: ?Sized, F>(s: Self, f: F) -> Result<MappedUniqueGuard<'a, U>, Self>
+    where
+        F: FnOnce(&mut T) -> Option<&mut U>,
+    {
+        let data = match f(unsafe { &mut *s.data }) {
+            Some(data) => data as *mut U,
+            None => return Err(s),
+        };
+        let borrow = s.borrow;
+        mem::forget(s);
+
+        Ok(MappedUniqueGuard { data, borrow })
+    }
+
+    /// Gets the inner [`AtomicBorrow`].
+    ///
+    /// This is an associated function to avoid conflicting with methods on `T`.
+    ///
+    /// # Safety
+    /// The borrow guards the data, which may belong to an
+    /// [`AtomicRefCell`](crate::AtomicRefCell), so references must not be released
+    /// through it unless they were also acquired through it.
+    #[inline]
+    pub unsafe fn get_borrow(s: &Self) -> &'a AtomicBorrow {
+        s.borrow
+    }
+}
+
+impl<'a, T: ?Sized> Deref for MappedUniqueGuard<'a, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.data }
+    }
+}
+
+impl<'a, T: ?Sized> DerefMut for MappedUniqueGuard<'a, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.data }
+    }
+}
+
+impl<'a, T: ?Sized> Drop for MappedUniqueGuard<'a, T> {
+    #[inline]
+    fn drop(&mut self) {
+        self.borrow.release_mut();
+    }
+}
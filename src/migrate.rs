@@ -0,0 +1,64 @@
+use std::sync::atomic::Ordering;
+
+use crate::{AtomicBorrow, SharedGuard};
+
+impl AtomicBorrow {
+    /// Copies the state of `from` to `to`, eg. when moving data to a new location while
+    /// resizing a structure.
+    ///
+    /// `from` should be quiesced, any borrow or release of it while migrating makes this
+    /// fail, leaving `to` unchanged. After success, guards of `from` must be moved over,
+    /// eg. by forgetting them and releasing `to` in their place, since `from` keeps its
+    /// state as well.
+    ///
+    /// Fails if `to` is borrowed. `to` must not be reachable by other threads until the
+    /// migration succeeded, since it's reset if `from` changes.
+    #[inline]
+    #[allow(clippy::result_unit_err)]
+    pub fn migrate_state(from: &AtomicBorrow, to: &AtomicBorrow) -> Result<(), ()> {
+        let state = from.borrow.load(Ordering::Acquire);
+
+        if to
+            .borrow
+            .compare_exchange(0, state, Ordering::AcqRel, Ordering::Relaxed)
+            .is_err()
+        {
+            return Err(());
+        }
+
+        // if `from` changed while copying, the copy may be stale
+        if from.borrow.load(Ordering::Acquire) != state {
+            to.borrow.store(0, Ordering::Release);
+            return Err(());
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, T> SharedGuard<'a, T> {
+    /// Moves the shared borrow held by `s` to `new_borrow`, and points it at `new_data`,
+    /// eg. after the guarded value was relocated while `s` was held.
+    ///
+    /// `new_borrow` is borrowed before the old borrow is released, so the value is
+    /// borrowed at every point in between. Returns `false`, leaving `s` unchanged, if
+    /// `new_borrow` is uniquely borrowed.
+    ///
+    /// This is an associated function to avoid conflicting with methods on `T`.
+    ///
+    /// # Safety
+    /// * Any borrows of `new_data` must be registered with `new_borrow`.
+    /// * `new_data` must be a valid pointer for the entire lifetime `'a`.
+    #[inline]
+    #[must_use]
+    pub unsafe fn rebind(s: &mut Self, new_borrow: &'a AtomicBorrow, new_data: *const T) -> bool {
+        if !new_borrow.borrow() {
+            return false;
+        }
+
+        s.borrow.release();
+        s.borrow = new_borrow;
+        s.data = new_data;
+        true
+    }
+}
@@ -0,0 +1,98 @@
+use std::{fmt::Write, sync::Mutex};
+
+use crate::{AtomicBorrow, BorrowState};
+
+/// A registered borrow.
+struct Entry {
+    name: &'static str,
+    borrow: &'static AtomicBorrow,
+    parent: Option<&'static AtomicBorrow>,
+}
+
+/// Registered borrows, in the order they were registered.
+static REGISTRY: Mutex<Vec<Entry>> = Mutex::new(Vec::new());
+
+impl AtomicBorrow {
+    /// Registers `self` under `name` for [`dump_all_borrow_states`], replacing the name
+    /// if `self` was already registered.
+    ///
+    /// Only `'static` borrows can be registered, since they are never unregistered.
+    ///
+    /// [`dump_all_borrow_states`]: Self::dump_all_borrow_states
+    pub fn register(&'static self, name: &'static str) {
+        self.register_entry(name, None);
+    }
+
+    /// Registers `self` under `name`, like [`register`](Self::register), as a child of
+    /// `parent`, eg. a field guarded by `self` inside a structure guarded by `parent`.
+    ///
+    /// The relation only shows up in [`dump_dot`](Self::dump_dot).
+    pub fn register_child(&'static self, name: &'static str, parent: &'static AtomicBorrow) {
+        self.register_entry(name, Some(parent));
+    }
+
+    fn register_entry(&'static self, name: &'static str, parent: Option<&'static AtomicBorrow>) {
+        let mut registry = REGISTRY.lock().unwrap_or_else(|err| err.into_inner());
+
+        match registry.iter_mut().find(|e| std::ptr::eq(e.borrow, self)) {
+            Some(entry) => {
+                entry.name = name;
+                entry.parent = parent;
+            }
+            None => registry.push(Entry {
+                name,
+                borrow: self,
+                parent,
+            }),
+        }
+    }
+
+    /// Returns the names and current states of all registered borrows, in the order
+    /// they were registered, eg. for a debug endpoint showing which locks are held.
+    ///
+    /// Each state is only a snapshot, and they aren't taken at the same time.
+    pub fn dump_all_borrow_states() -> Vec<(&'static str, BorrowState)> {
+        let registry = REGISTRY.lock().unwrap_or_else(|err| err.into_inner());
+        let states = registry.iter().map(|e| (e.name, e.borrow.state()));
+        states.collect()
+    }
+
+    /// Returns a Graphviz DOT graph of all registered borrows, colored by their current
+    /// state, with an edge from every parent to its children.
+    ///
+    /// Free borrows are green, shared ones yellow and unique ones red. Render it with
+    /// eg. `dot -Tsvg`. Like [`dump_all_borrow_states`](Self::dump_all_borrow_states),
+    /// the states are snapshots taken one after another.
+    pub fn dump_dot() -> String {
+        let registry = REGISTRY.lock().unwrap_or_else(|err| err.into_inner());
+        let mut dot = String::from("digraph borrows {\n    node [shape=box, style=filled];\n");
+
+        for (i, entry) in registry.iter().enumerate() {
+            let (state, color) = match entry.borrow.state() {
+                BorrowState::Unborrowed => (String::from("free"), "palegreen"),
+                BorrowState::Shared(n) => (format!("shared({})", n), "khaki"),
+                BorrowState::Unique => (String::from("unique"), "salmon"),
+            };
+
+            let name = entry.name.replace('\\', "\\\\").replace('"', "\\\"");
+            let _ = writeln!(
+                dot,
+                "    n{} [label=\"{}\\n{}\", fillcolor={}];",
+                i, name, state, color
+            );
+        }
+
+        for (i, entry) in registry.iter().enumerate() {
+            let parent = entry
+                .parent
+                .and_then(|p| registry.iter().position(|e| std::ptr::eq(e.borrow, p)));
+
+            if let Some(parent) = parent {
+                let _ = writeln!(dot, "    n{} -> n{};", parent, i);
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
@@ -0,0 +1,29 @@
+use std::io;
+
+use crate::AtomicBorrow;
+
+impl AtomicBorrow {
+    /// Tries to acquire a shared reference, returning an error of kind
+    /// [`WouldBlock`](io::ErrorKind::WouldBlock) on contention, for event loops that
+    /// already retry on it.
+    #[inline]
+    pub fn borrow_nonblocking(&self) -> io::Result<()> {
+        if self.borrow() {
+            Ok(())
+        } else {
+            Err(io::ErrorKind::WouldBlock.into())
+        }
+    }
+
+    /// Tries to acquire a unique reference, returning an error of kind
+    /// [`WouldBlock`](io::ErrorKind::WouldBlock) on contention, for event loops that
+    /// already retry on it.
+    #[inline]
+    pub fn borrow_mut_nonblocking(&self) -> io::Result<()> {
+        if self.borrow_mut() {
+            Ok(())
+        } else {
+            Err(io::ErrorKind::WouldBlock.into())
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use std::task::Waker;
+
+use futures_util::task::AtomicWaker;
+
+use crate::{AtomicBorrow, BorrowState};
+
+/// An [`AtomicBorrow`] that wakes a single registered task when it becomes free.
+///
+/// The inner borrow isn't exposed, so every release goes through `self` and wakes the
+/// task.
+#[derive(Debug, Default)]
+pub struct NotifyBorrow {
+    borrow: AtomicBorrow,
+    waker: AtomicWaker,
+}
+
+impl NotifyBorrow {
+    /// Creates a new `NotifyBorrow`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the current borrow state of `self`.
+    #[inline]
+    pub fn state(&self) -> BorrowState {
+        self.borrow.state()
+    }
+
+    /// Returns the number of shared references.
+    #[inline]
+    pub fn shared_count(&self) -> usize {
+        self.borrow.shared_count()
+    }
+
+    /// Returns true if there are any references.
+    #[inline]
+    pub fn is_borrowed(&self) -> bool {
+        self.borrow.is_borrowed()
+    }
+
+    /// Registers `waker` to be woken the next time `self` becomes free, replacing any
+    /// previously registered waker.
+    #[inline]
+    pub fn register_waker(&self, waker: &Waker) {
+        self.waker.register(waker);
+    }
+
+    /// Tries to acquire a shared reference.
+    ///
+    /// Returns `true` if the reference was acquired.
+    #[inline]
+    pub fn borrow(&self) -> bool {
+        if self.borrow.borrow() {
+            return true;
+        }
+
+        // a failed borrow briefly increments the counter, so a unique release in between
+        // doesn't see `self` become free, and the decrement here is what frees it
+        if !self.borrow.is_borrowed() {
+            self.waker.wake();
+        }
+
+        false
+    }
+
+    /// Tries to acquire a unique reference.
+    ///
+    /// Returns `true` if the reference was acquired.
+    #[inline]
+    pub fn borrow_mut(&self) -> bool {
+        self.borrow.borrow_mut()
+    }
+
+    /// Releases a shared reference, waking the registered task if `self` became free.
+    #[inline]
+    pub fn release(&self) {
+        if self.borrow.release_prev() == 1 {
+            self.waker.wake();
+        }
+    }
+
+    /// Releases a unique reference, waking the registered task if `self` became free.
+    #[inline]
+    pub fn release_mut(&self) {
+        if self.borrow.release_mut_prev() == AtomicBorrow::UNIQUE_MASK {
+            self.waker.wake();
+        }
+    }
+}
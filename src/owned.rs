@@ -0,0 +1,250 @@
+use std::{
+    mem,
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
+
+use crate::{AtomicBorrow, AtomicRefCell};
+
+/// An [`Arc`] wrapped [`AtomicRefCell`], handing out guards without lifetimes.
+#[derive(Debug, Default)]
+pub struct AtomicRc<T> {
+    cell: Arc<AtomicRefCell<T>>,
+}
+
+impl<T> AtomicRc<T> {
+    /// Creates a new `AtomicRc` containing `value`.
+    #[inline]
+    pub fn new(value: T) -> Self {
+        Self {
+            cell: Arc::new(AtomicRefCell::new(value)),
+        }
+    }
+
+    /// Tries to immutably borrow the value.
+    ///
+    /// The returned guard keeps the value alive.
+    #[inline]
+    pub fn borrow_owned(&self) -> Option<OwnedSharedGuard<T>> {
+        OwnedSharedGuard::try_new(self.cell.clone())
+    }
+
+    /// Tries to mutably borrow the value.
+    ///
+    /// The returned guard keeps the value alive.
+    #[inline]
+    pub fn borrow_mut_owned(&self) -> Option<OwnedUniqueGuard<T>> {
+        OwnedUniqueGuard::try_new(self.cell.clone())
+    }
+}
+
+impl<T> AtomicRefCell<T> {
+    /// Tries to immutably borrow the value of `this`, returning a guard holding a clone of
+    /// `this`.
+    ///
+    /// The guard is `'static` and keeps the value alive, even after every other `Arc` is
+    /// dropped. This is an associated function, like the methods of [`Arc`].
+    #[inline]
+    pub fn borrow_arc(this: &Arc<Self>) -> Option<OwnedSharedGuard<T>> {
+        OwnedSharedGuard::try_new(this.clone())
+    }
+
+    /// Tries to mutably borrow the value of `this`, returning a guard holding a clone of
+    /// `this`.
+    ///
+    /// The guard is `'static` and keeps the value alive, even after every other `Arc` is
+    /// dropped. This is an associated function, like the methods of [`Arc`].
+    #[inline]
+    pub fn borrow_mut_arc(this: &Arc<Self>) -> Option<OwnedUniqueGuard<T>> {
+        OwnedUniqueGuard::try_new(this.clone())
+    }
+}
+
+impl<T> Clone for AtomicRc<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            cell: self.cell.clone(),
+        }
+    }
+}
+
+impl<T> Deref for AtomicRc<T> {
+    type Target = AtomicRefCell<T>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.cell
+    }
+}
+
+/// A shared guard that owns both its data and its [`AtomicBorrow`].
+///
+/// Since nothing is borrowed externally, the guard has no lifetime and can be stored
+/// alongside other data without running into self-referential structs.
+pub struct OwnedSharedGuard<T> {
+    cell: Arc<AtomicRefCell<T>>,
+}
+
+impl<T> OwnedSharedGuard<T> {
+    /// Creates a new [`OwnedSharedGuard`], pairing `data` with a shared reference.
+    #[inline]
+    pub fn new(data: T) -> Self {
+        let cell = Arc::new(AtomicRefCell::new(data));
+        Self::try_new(cell).unwrap()
+    }
+
+    /// Pairs `data` with an owned shared reference, the same as [`new`](Self::new).
+    #[inline]
+    pub fn into_owned_shared(data: T) -> Self {
+        Self::new(data)
+    }
+
+    /// Tries to borrow the data in `cell`.
+    #[inline]
+    pub fn try_new(cell: Arc<AtomicRefCell<T>>) -> Option<Self> {
+        // SAFETY: the guard releases the reference acquired here
+        if unsafe { cell.get_borrow() }.borrow() {
+            Some(Self { cell })
+        } else {
+            None
+        }
+    }
+
+    /// Gets the inner [`AtomicBorrow`].
+    ///
+    /// # Safety
+    /// The borrow guards the value, so references must not be released through it
+    /// unless they were also acquired through it.
+    #[inline]
+    pub unsafe fn get_borrow(&self) -> &AtomicBorrow {
+        unsafe { self.cell.get_borrow() }
+    }
+
+    /// Gets the inner [`AtomicRefCell`].
+    #[inline]
+    pub fn get_cell(&self) -> &Arc<AtomicRefCell<T>> {
+        &self.cell
+    }
+
+    /// Releases the shared reference and returns the data.
+    ///
+    /// # Panics
+    /// If the cell is shared with other handles, eg. when the guard came from
+    /// [`AtomicRc::borrow_owned`]. See [`try_into_inner`](Self::try_into_inner).
+    #[inline]
+    #[track_caller]
+    pub fn into_inner(self) -> T {
+        match self.try_into_inner() {
+            Ok(data) => data,
+            Err(_) => panic!("the cell of an OwnedSharedGuard is shared with other handles"),
+        }
+    }
+
+    /// Releases the shared reference and returns the data, or returns `self` again if
+    /// the cell is shared with other handles.
+    #[inline]
+    pub fn try_into_inner(self) -> Result<T, Self> {
+        // SAFETY: `self` is forgotten, so the cell is only dropped once
+        let cell = unsafe { std::ptr::read(&self.cell) };
+        mem::forget(self);
+
+        match Arc::try_unwrap(cell) {
+            Ok(cell) => {
+                // SAFETY: acquired in `try_new`
+                unsafe { cell.get_borrow() }.release();
+                Ok(cell.into_inner())
+            }
+            Err(cell) => Err(Self { cell }),
+        }
+    }
+}
+
+impl<T> From<T> for OwnedSharedGuard<T> {
+    #[inline]
+    fn from(data: T) -> Self {
+        Self::new(data)
+    }
+}
+
+impl<T> Deref for OwnedSharedGuard<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.cell.as_ptr() }
+    }
+}
+
+impl<T> Drop for OwnedSharedGuard<T> {
+    #[inline]
+    fn drop(&mut self) {
+        // SAFETY: acquired in `try_new`
+        unsafe { self.cell.get_borrow() }.release();
+    }
+}
+
+/// A unique guard that owns both its data and its [`AtomicBorrow`].
+pub struct OwnedUniqueGuard<T> {
+    cell: Arc<AtomicRefCell<T>>,
+}
+
+impl<T> OwnedUniqueGuard<T> {
+    /// Creates a new [`OwnedUniqueGuard`], pairing `data` with a unique reference.
+    #[inline]
+    pub fn new(data: T) -> Self {
+        let cell = Arc::new(AtomicRefCell::new(data));
+        Self::try_new(cell).unwrap()
+    }
+
+    /// Tries to uniquely borrow the data in `cell`.
+    #[inline]
+    pub fn try_new(cell: Arc<AtomicRefCell<T>>) -> Option<Self> {
+        // SAFETY: the guard releases the reference acquired here
+        if unsafe { cell.get_borrow() }.borrow_mut() {
+            Some(Self { cell })
+        } else {
+            None
+        }
+    }
+
+    /// Gets the inner [`AtomicBorrow`].
+    ///
+    /// # Safety
+    /// The borrow guards the value, so references must not be released through it
+    /// unless they were also acquired through it.
+    #[inline]
+    pub unsafe fn get_borrow(&self) -> &AtomicBorrow {
+        unsafe { self.cell.get_borrow() }
+    }
+
+    /// Gets the inner [`AtomicRefCell`].
+    #[inline]
+    pub fn get_cell(&self) -> &Arc<AtomicRefCell<T>> {
+        &self.cell
+    }
+}
+
+impl<T> Deref for OwnedUniqueGuard<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.cell.as_ptr() }
+    }
+}
+
+impl<T> DerefMut for OwnedUniqueGuard<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.cell.as_ptr() }
+    }
+}
+
+impl<T> Drop for OwnedUniqueGuard<T> {
+    #[inline]
+    fn drop(&mut self) {
+        // SAFETY: acquired in `try_new`
+        unsafe { self.cell.get_borrow() }.release_mut();
+    }
+}
@@ -0,0 +1,48 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+    thread::{self, ThreadId},
+};
+
+use crate::AtomicBorrow;
+
+/// The threads holding unique borrows, keyed by the address of the [`AtomicBorrow`].
+fn registry() -> &'static Mutex<HashMap<usize, ThreadId>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<usize, ThreadId>>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+impl AtomicBorrow {
+    #[inline]
+    fn owner_key(&self) -> usize {
+        self as *const Self as usize
+    }
+
+    /// Records the current thread as the owner, must be called right after acquiring a
+    /// unique reference.
+    #[inline]
+    pub(crate) fn record_owner(&self) {
+        let mut registry = registry().lock().unwrap_or_else(|err| err.into_inner());
+        registry.insert(self.owner_key(), thread::current().id());
+    }
+
+    /// Removes the recorded owner, must be called while still holding the unique
+    /// reference, so it can't remove the owner of the next one.
+    #[inline]
+    pub(crate) fn clear_owner(&self) {
+        let mut registry = registry().lock().unwrap_or_else(|err| err.into_inner());
+        registry.remove(&self.owner_key());
+    }
+
+    /// Returns true if the unique reference is held by the current thread, eg. to check
+    /// before acquiring it again, which would never succeed.
+    ///
+    /// Only unique references acquired through [`borrow_mut`](Self::borrow_mut), or
+    /// methods built on it, and [`checked_borrow_mut`](Self::checked_borrow_mut) are
+    /// recorded, and the owner is the thread that acquired it, even if a guard was sent
+    /// to another thread since.
+    pub fn is_held_by_current_thread(&self) -> bool {
+        let registry = registry().lock().unwrap_or_else(|err| err.into_inner());
+        registry.get(&self.owner_key()) == Some(&thread::current().id())
+    }
+}
@@ -0,0 +1,54 @@
+use rayon::iter::{plumbing::UnindexedConsumer, IntoParallelRefIterator, ParallelIterator};
+
+use crate::AtomicBorrow;
+
+impl AtomicBorrow {
+    /// Tries to acquire a shared reference, returning a parallel iterator over `data`.
+    ///
+    /// The reference is held until the iterator has been driven to completion or dropped.
+    /// Since `data` is an immutable reference, the items are tied to its lifetime rather
+    /// than to the borrow, they stay valid after the iteration, while `self` merely tracks
+    /// the reference for the duration of the iteration itself.
+    #[inline]
+    pub fn par_borrow<'a, T: Sync>(&'a self, data: &'a [T]) -> Option<GuardedParIter<'a, T>> {
+        if self.borrow() {
+            Some(GuardedParIter { data, borrow: self })
+        } else {
+            None
+        }
+    }
+}
+
+/// A [`ParallelIterator`] over a slice that holds a shared reference while iterating.
+///
+/// The borrow only covers the iteration, the items borrow from the slice passed to
+/// [`AtomicBorrow::par_borrow`] and may outlive it.
+pub struct GuardedParIter<'a, T> {
+    data: &'a [T],
+    borrow: &'a AtomicBorrow,
+}
+
+impl<'a, T: Sync> ParallelIterator for GuardedParIter<'a, T> {
+    type Item = &'a T;
+
+    #[inline]
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        // `self` is dropped after the iteration completes, releasing the reference
+        self.data.par_iter().drive_unindexed(consumer)
+    }
+
+    #[inline]
+    fn opt_len(&self) -> Option<usize> {
+        Some(self.data.len())
+    }
+}
+
+impl<'a, T> Drop for GuardedParIter<'a, T> {
+    #[inline]
+    fn drop(&mut self) {
+        self.borrow.release();
+    }
+}
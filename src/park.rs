@@ -0,0 +1,114 @@
+use std::{
+    sync::atomic::Ordering,
+    time::{Duration, Instant},
+};
+
+use parking_lot_core::{DEFAULT_PARK_TOKEN, DEFAULT_UNPARK_TOKEN};
+
+use crate::{waiters::WaiterCounts, AtomicBorrow};
+
+/// The threads parked on each borrow, so releases can skip `unpark_all`.
+static PARKED: WaiterCounts = WaiterCounts::new();
+
+impl AtomicBorrow {
+    #[inline]
+    fn park_key(&self) -> usize {
+        self as *const Self as usize
+    }
+
+    /// Parks the current thread until `self` might be free to uniquely borrow, or until
+    /// `deadline` is reached.
+    #[cold]
+    fn park_mut(&self, deadline: Option<Instant>) {
+        PARKED.add(self);
+
+        // `SeqCst`, so either this sees the release, or the release sees the count
+        let validate = || self.borrow.load(Ordering::SeqCst) != 0;
+
+        // SAFETY: the callbacks neither panic nor call into `parking_lot_core`.
+        unsafe {
+            parking_lot_core::park(
+                self.park_key(),
+                validate,
+                || {},
+                |_, _| {},
+                DEFAULT_PARK_TOKEN,
+                deadline,
+            );
+        }
+
+        PARKED.remove(self);
+    }
+
+    /// Wakes all threads parked on `self`.
+    #[inline]
+    pub(crate) fn unpark(&self) {
+        if !PARKED.may_have_waiters(self) {
+            return;
+        }
+
+        // SAFETY: the callback neither panics nor calls into `parking_lot_core`.
+        unsafe {
+            parking_lot_core::unpark_all(self.park_key(), DEFAULT_UNPARK_TOKEN);
+        }
+    }
+
+    /// Unparks one thread parked on `self`, returning `true` if there was one.
+    #[cfg(all(test, not(feature = "single-threaded")))]
+    pub(crate) fn unpark_one(&self) -> bool {
+        // SAFETY: the callback neither panics nor calls into `parking_lot_core`.
+        let result =
+            unsafe { parking_lot_core::unpark_one(self.park_key(), |_| DEFAULT_UNPARK_TOKEN) };
+
+        result.unparked_threads == 1
+    }
+
+    /// Blocks until a unique reference can be acquired.
+    ///
+    /// Spins for a short while before parking the thread.
+    #[inline]
+    pub fn blocking_borrow_mut(&self) {
+        self.blocking_borrow_mut_with(Self::SPIN_COUNT);
+    }
+
+    /// Blocks until a unique reference can be acquired.
+    ///
+    /// Spins `spin_threshold` times before parking the thread, short holds favor a high
+    /// threshold while long holds favor a low one.
+    #[inline]
+    pub fn blocking_borrow_mut_with(&self, spin_threshold: usize) {
+        for _ in 0..spin_threshold {
+            if self.borrow_mut() {
+                return;
+            }
+
+            std::hint::spin_loop();
+        }
+
+        while !self.borrow_mut() {
+            self.park_mut(None);
+        }
+    }
+
+    /// Blocks until a unique reference can be acquired, or `timeout` has passed.
+    ///
+    /// Parks the thread immediately rather than spinning.
+    ///
+    /// Returns `true` if the reference was acquired.
+    #[inline]
+    pub fn park_borrow_mut_timeout(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            if self.borrow_mut() {
+                return true;
+            }
+
+            if Instant::now() >= deadline {
+                return false;
+            }
+
+            self.park_mut(Some(deadline));
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::{
+    ops::Deref,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::AtomicBorrow;
+
+/// An [`AtomicBorrow`] tracking the highest number of shared references it has had at
+/// once, eg. for sizing reader pools, and how many were acquired in total.
+///
+/// Only acquisitions through [`PeakBorrow::borrow`] are tracked, not those through the
+/// inner [`AtomicBorrow`].
+#[derive(Debug, Default)]
+pub struct PeakBorrow {
+    borrow: AtomicBorrow,
+    peak: AtomicUsize,
+    acquisitions: AtomicUsize,
+}
+
+impl PeakBorrow {
+    const_fn! {
+        /// Creates a new `PeakBorrow`.
+        #[inline]
+        pub fn new() -> Self {
+            Self {
+                borrow: AtomicBorrow::new(),
+                peak: AtomicUsize::new(0),
+                acquisitions: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    /// Tries to acquire a shared reference, updating the metrics.
+    ///
+    /// Returns `true` if the reference was acquired.
+    #[inline]
+    #[track_caller]
+    pub fn borrow(&self) -> bool {
+        if !self.borrow.borrow() {
+            return false;
+        }
+
+        self.acquisitions.fetch_add(1, Ordering::Relaxed);
+
+        // the count right after acquiring, so a peak can be missed if other references
+        // are released in between
+        let shared = self.borrow.borrow.load(Ordering::Relaxed) & AtomicBorrow::SHARED_MASK;
+
+        // skip the write while below the peak, which is the common case
+        if shared > self.peak.load(Ordering::Relaxed) {
+            self.peak.fetch_max(shared, Ordering::Relaxed);
+        }
+
+        true
+    }
+
+    /// Returns the highest number of shared references observed at once.
+    #[inline]
+    pub fn peak_shared(&self) -> usize {
+        self.peak.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of shared references acquired.
+    #[inline]
+    pub fn acquisitions(&self) -> usize {
+        self.acquisitions.load(Ordering::Relaxed)
+    }
+
+    /// Resets the peak to `0`, returning the previous peak.
+    #[inline]
+    pub fn reset_peak(&self) -> usize {
+        self.peak.swap(0, Ordering::Relaxed)
+    }
+
+    /// Resets all metrics to `0`, eg. to sample them per interval.
+    ///
+    /// The metrics are reset one after the other, so acquisitions racing with the reset
+    /// may be counted in one metric but not the other.
+    #[inline]
+    pub fn reset_metrics(&self) {
+        self.peak.store(0, Ordering::Relaxed);
+        self.acquisitions.store(0, Ordering::Relaxed);
+    }
+}
+
+impl Deref for PeakBorrow {
+    type Target = AtomicBorrow;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.borrow
+    }
+}
@@ -0,0 +1,216 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, OnceLock,
+    },
+};
+
+use crate::{jitter::xorshift, AtomicBorrow};
+
+/// How to wait for an [`AtomicBorrow`], bundling the spin count, backoff, jitter and
+/// fairness in one place.
+///
+/// Used with [`AtomicBorrow::borrow_with_policy`] and
+/// [`AtomicBorrow::borrow_mut_with_policy`].
+///
+/// # Example
+/// ```
+/// use atomic_borrow::{AtomicBorrow, BorrowPolicy};
+///
+/// const POLICY: BorrowPolicy = BorrowPolicy::new().spin_count(64).backoff(true);
+///
+/// let borrow = AtomicBorrow::new();
+/// borrow.borrow_mut_with_policy(&POLICY);
+/// borrow.release_mut();
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BorrowPolicy {
+    spin_count: usize,
+    backoff: bool,
+    jitter: bool,
+    fair: bool,
+}
+
+impl BorrowPolicy {
+    const MAX_BACKOFF_SHIFT: u32 = 8;
+
+    /// Creates a policy waiting the same way as [`AtomicBorrow::spin_borrow`].
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            spin_count: AtomicBorrow::SPIN_COUNT,
+            backoff: false,
+            jitter: false,
+            fair: false,
+        }
+    }
+
+    /// Sets how many failed attempts are spun on before yielding the thread instead.
+    ///
+    /// `0` yields immediately, `usize::MAX` never yields.
+    #[inline]
+    pub const fn spin_count(mut self, spin_count: usize) -> Self {
+        self.spin_count = spin_count;
+        self
+    }
+
+    /// Sets whether the number of spins between attempts doubles after each failed
+    /// attempt, up to 256.
+    #[inline]
+    pub const fn backoff(mut self, backoff: bool) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Sets whether the number of spins between attempts is randomized, so waiting
+    /// threads don't all retry at once.
+    #[inline]
+    pub const fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Sets whether waiters acquire in the order they started waiting.
+    ///
+    /// Fair waiters queue up in a global registry keyed by the address of the
+    /// [`AtomicBorrow`], which is slower, and only orders them among each other. Plain
+    /// acquisitions like [`AtomicBorrow::borrow_mut`] can still overtake them.
+    #[inline]
+    pub const fn fair(mut self, fair: bool) -> Self {
+        self.fair = fair;
+        self
+    }
+
+    /// Returns the number of spins to wait after failed attempt number `attempt`.
+    #[inline]
+    fn spins(&self, attempt: usize) -> u32 {
+        let spins = match self.backoff {
+            true => 1 << (attempt as u32).min(Self::MAX_BACKOFF_SHIFT),
+            false => 1,
+        };
+
+        match self.jitter {
+            true => xorshift() % spins + 1,
+            false => spins,
+        }
+    }
+}
+
+impl Default for BorrowPolicy {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The tickets of fair waiters on an [`AtomicBorrow`].
+#[derive(Debug, Default)]
+struct Tickets {
+    // only incremented under the registry lock
+    next: AtomicUsize,
+    serving: AtomicUsize,
+}
+
+/// Tickets of fair waiters, keyed by the address of the [`AtomicBorrow`].
+fn registry() -> &'static Mutex<HashMap<usize, Arc<Tickets>>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<usize, Arc<Tickets>>>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+/// A place in the queue of fair waiters, passed on to the next waiter when dropped.
+struct Ticket {
+    key: usize,
+    tickets: Arc<Tickets>,
+    ticket: usize,
+}
+
+impl Ticket {
+    #[inline]
+    fn take(borrow: &AtomicBorrow) -> Self {
+        let key = borrow as *const AtomicBorrow as usize;
+
+        let mut registry = registry().lock().unwrap();
+        let tickets = registry.entry(key).or_default().clone();
+        let ticket = tickets.next.fetch_add(1, Ordering::Relaxed);
+
+        Self {
+            key,
+            tickets,
+            ticket,
+        }
+    }
+
+    #[inline]
+    fn is_served(&self) -> bool {
+        self.tickets.serving.load(Ordering::Acquire) == self.ticket
+    }
+}
+
+impl Drop for Ticket {
+    #[inline]
+    fn drop(&mut self) {
+        let mut registry = registry().lock().unwrap();
+        let serving = self.tickets.serving.fetch_add(1, Ordering::Release) + 1;
+
+        // nobody is queued anymore
+        if self.tickets.next.load(Ordering::Relaxed) == serving {
+            registry.remove(&self.key);
+        }
+    }
+}
+
+impl AtomicBorrow {
+    /// Waits until `acquire` succeeds, the way `policy` describes.
+    #[inline]
+    fn wait_with_policy(&self, policy: &BorrowPolicy, acquire: impl Fn(&Self) -> bool) {
+        if !policy.fair && acquire(self) {
+            return;
+        }
+
+        let ticket = policy.fair.then(|| Ticket::take(self));
+        let mut attempt = 0;
+
+        loop {
+            if ticket.as_ref().is_none_or(Ticket::is_served) && acquire(self) {
+                return;
+            }
+
+            if attempt < policy.spin_count {
+                for _ in 0..policy.spins(attempt) {
+                    std::hint::spin_loop();
+                }
+
+                attempt += 1;
+            } else {
+                std::thread::yield_now();
+            }
+        }
+    }
+
+    /// Waits until a shared reference can be acquired, as described by `policy`.
+    #[inline]
+    pub fn borrow_with_policy(&self, policy: &BorrowPolicy) {
+        self.wait_with_policy(policy, Self::borrow);
+    }
+
+    /// Waits until a unique reference can be acquired, as described by `policy`.
+    #[inline]
+    pub fn borrow_mut_with_policy(&self, policy: &BorrowPolicy) {
+        self.wait_with_policy(policy, Self::borrow_mut);
+    }
+
+    /// Returns the number of fair waiters queued on `self`.
+    #[cfg(all(test, not(feature = "single-threaded")))]
+    pub(crate) fn fair_waiters(&self) -> usize {
+        let key = self as *const Self as usize;
+
+        match registry().lock().unwrap().get(&key) {
+            Some(tickets) => {
+                let next = tickets.next.load(Ordering::Relaxed);
+                next - tickets.serving.load(Ordering::Relaxed)
+            }
+            None => 0,
+        }
+    }
+}
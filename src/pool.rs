@@ -0,0 +1,199 @@
+use std::{
+    cell::UnsafeCell,
+    fmt,
+    mem::MaybeUninit,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+use crate::{AtomicBorrow, SharedGuard};
+
+struct Slot<T> {
+    borrow: AtomicBorrow,
+    // both only modified under a unique borrow, so `borrow` orders them
+    init: AtomicBool,
+    generation: AtomicUsize,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+impl<T> Slot<T> {
+    #[inline]
+    fn new() -> Self {
+        Self {
+            borrow: AtomicBorrow::new(),
+            init: AtomicBool::new(false),
+            generation: AtomicUsize::new(0),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    /// Returns true if the slot holds the value of `slot`, must be called under a borrow.
+    #[inline]
+    fn holds(&self, slot: SlotRef) -> bool {
+        self.init.load(Ordering::Relaxed)
+            && self.generation.load(Ordering::Relaxed) == slot.generation
+    }
+}
+
+/// A handle to a value in a [`SlotPool`].
+///
+/// Handles stay unique after the value is removed, so a stale handle never refers to a
+/// value inserted later into the same slot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SlotRef {
+    index: usize,
+    generation: usize,
+}
+
+impl SlotRef {
+    /// Returns the index of the slot.
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the generation of the slot, bumped every time a value is removed from it.
+    #[inline]
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+}
+
+/// A fixed size pool of `N` values, where the occupancy of each slot is tracked by an
+/// [`AtomicBorrow`].
+pub struct SlotPool<T, const N: usize> {
+    slots: [Slot<T>; N],
+}
+
+unsafe impl<T: Send, const N: usize> Send for SlotPool<T, N> {}
+#[cfg(not(feature = "single-threaded"))]
+unsafe impl<T: Send + Sync, const N: usize> Sync for SlotPool<T, N> {}
+
+impl<T, const N: usize> SlotPool<T, N> {
+    /// Creates a new empty `SlotPool`.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            slots: std::array::from_fn(|_| Slot::new()),
+        }
+    }
+
+    /// Inserts `value` into a free slot, returning a handle to it.
+    ///
+    /// Slots that are borrowed by other threads are skipped. Returns `None`, dropping
+    /// `value`, if no free slot was found.
+    #[inline]
+    pub fn insert(&self, value: T) -> Option<SlotRef> {
+        for (index, slot) in self.slots.iter().enumerate() {
+            if slot.init.load(Ordering::Relaxed) || !slot.borrow.borrow_mut() {
+                continue;
+            }
+
+            // another thread may have filled the slot since we checked
+            if slot.init.load(Ordering::Relaxed) {
+                slot.borrow.release_mut();
+                continue;
+            }
+
+            unsafe { (*slot.value.get()).write(value) };
+            slot.init.store(true, Ordering::Relaxed);
+            let generation = slot.generation.load(Ordering::Relaxed);
+            slot.borrow.release_mut();
+
+            return Some(SlotRef { index, generation });
+        }
+
+        None
+    }
+
+    /// Borrows the value of `slot`.
+    ///
+    /// Returns `None` if the value was removed, or the slot is being removed from.
+    #[inline]
+    pub fn get(&self, slot: SlotRef) -> Option<SharedGuard<'_, T>> {
+        let entry = self.slots.get(slot.index)?;
+
+        if !entry.borrow.borrow() {
+            return None;
+        }
+
+        if !entry.holds(slot) {
+            entry.borrow.release();
+            return None;
+        }
+
+        Some(SharedGuard {
+            data: entry.value.get() as *const T,
+            borrow: &entry.borrow,
+        })
+    }
+
+    /// Returns true if the value of `slot` hasn't been removed.
+    ///
+    /// Only a snapshot, and `false` while the slot is being removed from.
+    #[inline]
+    pub fn contains(&self, slot: SlotRef) -> bool {
+        self.get(slot).is_some()
+    }
+
+    /// Removes the value of `slot`, waiting for any readers to release it.
+    ///
+    /// Returns `None` if the value was already removed.
+    #[inline]
+    pub fn remove(&self, slot: SlotRef) -> Option<T> {
+        let entry = self.slots.get(slot.index)?;
+        entry.borrow.spin_borrow_mut();
+
+        let value = if entry.holds(slot) {
+            entry.init.store(false, Ordering::Relaxed);
+            entry.generation.fetch_add(1, Ordering::Relaxed);
+            Some(unsafe { (*entry.value.get()).assume_init_read() })
+        } else {
+            None
+        };
+
+        entry.borrow.release_mut();
+        value
+    }
+
+    /// Returns the number of occupied slots.
+    ///
+    /// Only a snapshot, other threads may insert or remove at any time.
+    #[inline]
+    pub fn len(&self) -> usize {
+        let occupied = |slot: &&Slot<T>| slot.init.load(Ordering::Relaxed);
+        self.slots.iter().filter(occupied).count()
+    }
+
+    /// Returns true if no slots are occupied.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T, const N: usize> Default for SlotPool<T, N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for SlotPool<T, N> {
+    #[inline]
+    fn drop(&mut self) {
+        for slot in &mut self.slots {
+            if *slot.init.get_mut() {
+                unsafe { slot.value.get_mut().assume_init_drop() };
+            }
+        }
+    }
+}
+
+impl<T, const N: usize> fmt::Debug for SlotPool<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlotPool")
+            .field("len", &self.len())
+            .field("capacity", &N)
+            .finish()
+    }
+}
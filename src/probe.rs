@@ -0,0 +1,18 @@
+use crate::AtomicBorrow;
+
+/// USDT probes, named `atomic_borrow:<probe>`.
+#[usdt::provider(provider = "atomic_borrow")]
+mod probes {
+    /// Fired when an acquisition stops spinning and starts yielding, with the address of
+    /// the borrow and whether a unique reference was wanted.
+    fn contended(borrow: usize, unique: u8) {}
+}
+
+impl AtomicBorrow {
+    /// Fires the `contended` probe, which costs a single load while no tracer is attached.
+    #[inline]
+    pub(crate) fn probe_contended(&self, unique: bool) {
+        let borrow = self as *const Self as usize;
+        probes::contended!(|| (borrow, unique as u8));
+    }
+}
@@ -0,0 +1,56 @@
+use std::{fmt, ops::Deref};
+
+use crate::UniqueGuard;
+
+impl<'a, T> UniqueGuard<'a, T> {
+    /// Temporarily reborrows the data as shared, without touching the borrow.
+    ///
+    /// The returned [`SharedRef`] borrows `self` mutably, so the data can't be mutated
+    /// through `self` while it exists.
+    #[inline]
+    pub fn reborrow_shared(&mut self) -> SharedRef<'_, T> {
+        SharedRef {
+            data: unsafe { &*self.data },
+        }
+    }
+}
+
+/// A shared reference reborrowed from a [`UniqueGuard`].
+///
+/// Created by [`UniqueGuard::reborrow_shared`].
+pub struct SharedRef<'b, T> {
+    data: &'b T,
+}
+
+impl<'b, T> Clone for SharedRef<'b, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'b, T> Copy for SharedRef<'b, T> {}
+
+impl<'b, T> SharedRef<'b, T> {
+    /// Gets the reference to the data, with the lifetime of the reborrow.
+    #[inline]
+    pub fn get(self) -> &'b T {
+        self.data
+    }
+}
+
+impl<'b, T> Deref for SharedRef<'b, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.data
+    }
+}
+
+impl<'b, T: fmt::Debug> fmt::Debug for SharedRef<'b, T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.data, f)
+    }
+}
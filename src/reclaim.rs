@@ -0,0 +1,103 @@
+use std::{
+    fmt,
+    ops::Deref,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use crate::AtomicBorrow;
+
+type Hook = Box<dyn FnOnce() + Send>;
+
+/// An [`AtomicBorrow`] that runs a hook once the last shared reference is released.
+///
+/// This allows deferring cleanup, e.g. in epoch based reclamation, until no readers
+/// remain.
+#[derive(Default)]
+pub struct ReclaimBorrow {
+    borrow: AtomicBorrow,
+    has_hook: AtomicBool,
+    hook: Mutex<Option<Hook>>,
+}
+
+impl ReclaimBorrow {
+    /// Creates a new `ReclaimBorrow`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets `f` to be called the next time releasing a shared reference leaves none
+    /// remaining, replacing any previously set hook.
+    #[inline]
+    pub fn on_last_release(&self, f: impl FnOnce() + Send + 'static) {
+        let mut hook = self.hook.lock().unwrap();
+        *hook = Some(Box::new(f));
+        self.has_hook.store(true, Ordering::Release);
+    }
+
+    /// Releases a shared reference, calling the hook if it was the last.
+    ///
+    /// The hook runs while `self` is uniquely borrowed, so no reader can acquire a
+    /// reference until it returns. If a reader borrows again before the hook gets to
+    /// run, the hook is left for the next time the last reference is released.
+    #[inline]
+    pub fn release(&self) {
+        let prev = self.borrow.release_prev();
+
+        if prev == 1 && self.has_hook.load(Ordering::Acquire) {
+            self.run_hook();
+        }
+    }
+
+    #[cold]
+    fn run_hook(&self) {
+        // a reader may have borrowed since the release, claim the free state so none can
+        // while the hook runs
+        let claimed = self
+            .borrow
+            .borrow
+            .compare_exchange(
+                0,
+                AtomicBorrow::UNIQUE_MASK,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            )
+            .is_ok();
+
+        if !claimed {
+            return;
+        }
+
+        let mut hook = self.hook.lock().unwrap();
+        let taken = hook.take();
+        self.has_hook.store(false, Ordering::Release);
+        drop(hook);
+
+        if let Some(hook) = taken {
+            hook();
+        }
+
+        self.borrow.release_mut();
+    }
+}
+
+impl Deref for ReclaimBorrow {
+    type Target = AtomicBorrow;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.borrow
+    }
+}
+
+impl fmt::Debug for ReclaimBorrow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReclaimBorrow")
+            .field("borrow", &self.borrow)
+            .field("has_hook", &self.has_hook.load(Ordering::Relaxed))
+            .finish()
+    }
+}
@@ -0,0 +1,120 @@
+use std::{
+    marker::PhantomData,
+    ops::Deref,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::AtomicBorrow;
+
+/// Returns a non-zero id unique to the current thread, never reused.
+#[inline]
+fn thread_id() -> usize {
+    static NEXT: AtomicUsize = AtomicUsize::new(1);
+
+    thread_local! {
+        static ID: usize = NEXT.fetch_add(1, Ordering::Relaxed);
+    }
+
+    ID.with(|id| *id)
+}
+
+/// An [`AtomicBorrow`] whose unique reference can be acquired again by the thread
+/// holding it.
+///
+/// Each nested [`ReentrantUniqueGuard`] increases the recursion depth, and the unique
+/// reference is only released when the outermost guard is dropped.
+#[derive(Debug, Default)]
+pub struct ReentrantBorrow {
+    borrow: AtomicBorrow,
+    // only modified by the owning thread, under the unique borrow
+    owner: AtomicUsize,
+    depth: AtomicUsize,
+}
+
+impl ReentrantBorrow {
+    const_fn! {
+        /// Creates a new `ReentrantBorrow`.
+        #[inline]
+        pub fn new() -> Self {
+            Self {
+                borrow: AtomicBorrow::new(),
+                owner: AtomicUsize::new(0),
+                depth: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    /// Tries to acquire a unique reference, succeeding if it's free or already held by
+    /// the current thread.
+    #[inline]
+    pub fn reentrant_borrow_mut(&self) -> Option<ReentrantUniqueGuard<'_>> {
+        let id = thread_id();
+
+        // only the current thread ever stores its own id
+        if self.owner.load(Ordering::Relaxed) == id {
+            self.depth.fetch_add(1, Ordering::Relaxed);
+        } else {
+            if !self.borrow.borrow_mut() {
+                return None;
+            }
+
+            self.owner.store(id, Ordering::Relaxed);
+            self.depth.store(1, Ordering::Relaxed);
+        }
+
+        Some(ReentrantUniqueGuard {
+            borrow: self,
+            marker: PhantomData,
+        })
+    }
+
+    /// Returns the recursion depth of the current unique reference, or `0` if there is
+    /// none.
+    ///
+    /// Only exact on the thread holding it.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.depth.load(Ordering::Relaxed)
+    }
+}
+
+impl Deref for ReentrantBorrow {
+    type Target = AtomicBorrow;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.borrow
+    }
+}
+
+/// A possibly nested unique reference of a [`ReentrantBorrow`].
+///
+/// Nested guards alias each other, so like a reentrant mutex it doesn't give mutable
+/// access to any data. It can't be sent to other threads, since it must be dropped by
+/// the thread holding the borrow.
+///
+/// Created by [`ReentrantBorrow::reentrant_borrow_mut`].
+#[derive(Debug)]
+pub struct ReentrantUniqueGuard<'a> {
+    borrow: &'a ReentrantBorrow,
+    marker: PhantomData<*const ()>,
+}
+
+impl<'a> ReentrantUniqueGuard<'a> {
+    /// Gets the [`ReentrantBorrow`].
+    #[inline]
+    pub fn get_borrow(&self) -> &'a ReentrantBorrow {
+        self.borrow
+    }
+}
+
+impl<'a> Drop for ReentrantUniqueGuard<'a> {
+    #[inline]
+    fn drop(&mut self) {
+        if self.borrow.depth.fetch_sub(1, Ordering::Relaxed) == 1 {
+            // cleared before releasing, so the next owner never sees our id
+            self.borrow.owner.store(0, Ordering::Relaxed);
+            self.borrow.borrow.release_mut();
+        }
+    }
+}
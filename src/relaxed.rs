@@ -0,0 +1,108 @@
+use std::sync::atomic::Ordering;
+
+use crate::AtomicBorrow;
+
+/// An [`AtomicBorrow`] where every operation uses [`Ordering::Relaxed`].
+///
+/// The counter still guarantees that a unique reference is never held together with
+/// any other reference, but acquiring and releasing don't synchronize with each other.
+/// This is for lock-free structures where the real synchronization happens elsewhere,
+/// eg. through sequence numbers.
+///
+/// # Requirements
+/// Accessing the borrowed data is only sound if the caller establishes the
+/// happens-before relations themselves. The simplest way is to pair every successful
+/// borrow with a [`fence(Acquire)`](std::sync::atomic::fence) after it, and every
+/// release with a [`fence(Release)`](std::sync::atomic::fence) before it.
+///
+/// None of the hooks of [`AtomicBorrow`] run, so releases don't wake blocked threads
+/// or futures. The inner [`AtomicBorrow`] isn't exposed, so nothing can wait on a
+/// `RelaxedBorrow` in the first place.
+#[derive(Debug, Default)]
+pub struct RelaxedBorrow {
+    borrow: AtomicBorrow,
+}
+
+impl RelaxedBorrow {
+    const_fn! {
+        /// Creates a new `RelaxedBorrow`.
+        #[inline]
+        pub fn new() -> Self {
+            Self {
+                borrow: AtomicBorrow::new(),
+            }
+        }
+    }
+
+    /// Returns the number of shared references.
+    #[inline]
+    pub fn shared_count(&self) -> usize {
+        self.borrow.shared_count()
+    }
+
+    /// Returns true if there are any references.
+    #[inline]
+    pub fn is_borrowed(&self) -> bool {
+        self.borrow.is_borrowed()
+    }
+
+    /// Tries to acquire a shared reference, without synchronizing.
+    ///
+    /// Returns `true` if the reference was acquired.
+    #[inline]
+    pub fn borrow(&self) -> bool {
+        let prev = self.borrow.borrow.fetch_add(1, Ordering::Relaxed);
+
+        if prev & AtomicBorrow::SHARED_MASK == AtomicBorrow::SHARED_MASK {
+            self.borrow.borrow.fetch_sub(1, Ordering::Relaxed);
+            panic!("borrow counter overflowed");
+        }
+
+        if prev & AtomicBorrow::UNIQUE_MASK != 0 {
+            self.borrow.borrow.fetch_sub(1, Ordering::Relaxed);
+            false
+        } else {
+            true
+        }
+    }
+
+    /// Tries to acquire a unique reference, without synchronizing.
+    ///
+    /// Returns `true` if the reference was acquired.
+    #[inline]
+    pub fn borrow_mut(&self) -> bool {
+        self.borrow
+            .borrow
+            .compare_exchange(
+                0,
+                AtomicBorrow::UNIQUE_MASK,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            )
+            .is_ok()
+    }
+
+    /// Releases a shared reference, without synchronizing.
+    #[inline]
+    pub fn release(&self) {
+        let prev = self.borrow.borrow.fetch_sub(1, Ordering::Relaxed);
+        debug_assert_ne!(
+            prev, 0,
+            "borrow counter underflow, this means you released more times than you borrowed"
+        );
+    }
+
+    /// Releases a unique reference, without synchronizing.
+    #[inline]
+    pub fn release_mut(&self) {
+        let prev = self
+            .borrow
+            .borrow
+            .fetch_and(!AtomicBorrow::UNIQUE_MASK, Ordering::Relaxed);
+        debug_assert_ne!(
+            prev & AtomicBorrow::UNIQUE_MASK,
+            0,
+            "unique release of shared borrow"
+        );
+    }
+}
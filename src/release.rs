@@ -0,0 +1,103 @@
+use crate::{AtomicBorrow, SharedGuard, UniqueGuard};
+
+impl AtomicBorrow {
+    /// Returns a [`ReleaseGuard`] releasing a shared reference when dropped.
+    ///
+    /// The shared reference must already be held by the caller.
+    #[inline]
+    pub fn defer_release(&self) -> ReleaseGuard<'_> {
+        ReleaseGuard {
+            borrow: self,
+            unique: false,
+        }
+    }
+
+    /// Returns a [`ReleaseGuard`] releasing a unique reference when dropped.
+    ///
+    /// The unique reference must already be held by the caller.
+    #[inline]
+    pub fn defer_release_mut(&self) -> ReleaseGuard<'_> {
+        ReleaseGuard {
+            borrow: self,
+            unique: true,
+        }
+    }
+}
+
+impl<'a, T> SharedGuard<'a, T> {
+    /// Splits the guard into a pointer to the data and a [`ReleaseGuard`] keeping the
+    /// shared reference.
+    ///
+    /// The pointer may be read from for as long as the release guard is alive.
+    ///
+    /// This is an associated function to avoid conflicting with methods on `T`.
+    #[inline]
+    pub fn into_release_guard(s: Self) -> (*const T, ReleaseGuard<'a>) {
+        let borrow = s.borrow;
+        (s.forget(), borrow.defer_release())
+    }
+}
+
+impl<'a, T> UniqueGuard<'a, T> {
+    /// Splits the guard into a pointer to the data and a [`ReleaseGuard`] keeping the
+    /// unique reference.
+    ///
+    /// The pointer may be read from and written to for as long as the release guard is
+    /// alive.
+    ///
+    /// This is an associated function to avoid conflicting with methods on `T`.
+    #[inline]
+    pub fn into_release_guard(s: Self) -> (*mut T, ReleaseGuard<'a>) {
+        let borrow = s.borrow;
+        (s.forget(), borrow.defer_release_mut())
+    }
+}
+
+/// Releases a reference of an [`AtomicBorrow`] when dropped, unless cancelled.
+///
+/// Unlike the guards it holds no data pointer, so it's [`Send`] whenever `AtomicBorrow`
+/// is [`Sync`], and can be held across an `.await` that moves the future to another
+/// thread. The data is then accessed separately, eg. through the pointer returned by
+/// [`SharedGuard::into_release_guard`].
+///
+/// Created by [`AtomicBorrow::defer_release`] and [`AtomicBorrow::defer_release_mut`].
+#[derive(Debug)]
+#[must_use = "dropping a release guard releases the reference immediately"]
+pub struct ReleaseGuard<'a> {
+    borrow: &'a AtomicBorrow,
+    unique: bool,
+}
+
+impl<'a> ReleaseGuard<'a> {
+    /// Gets the inner [`AtomicBorrow`].
+    #[inline]
+    pub fn get_borrow(&self) -> &'a AtomicBorrow {
+        self.borrow
+    }
+
+    /// Returns `true` if a unique reference is released, `false` if a shared one is.
+    #[inline]
+    pub fn is_unique(&self) -> bool {
+        self.unique
+    }
+
+    /// Cancels the release, handing the responsibility for it back to the caller.
+    ///
+    /// Returns the [`AtomicBorrow`] the reference is held on.
+    #[inline]
+    pub fn cancel(self) -> &'a AtomicBorrow {
+        let borrow = self.borrow;
+        std::mem::forget(self);
+        borrow
+    }
+}
+
+impl<'a> Drop for ReleaseGuard<'a> {
+    #[inline]
+    fn drop(&mut self) {
+        match self.unique {
+            true => self.borrow.release_mut(),
+            false => self.borrow.release(),
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{AtomicBorrow, BorrowState};
+
+/// An [`AtomicBorrow`] that can be revoked.
+///
+/// Once revoked new borrows fail, while existing borrows are expected to notice
+/// [`RevocableBorrow::is_revoked`] and release as soon as possible. The inner borrow
+/// isn't exposed, so nothing can acquire it past the revocation.
+#[derive(Debug, Default)]
+pub struct RevocableBorrow {
+    borrow: AtomicBorrow,
+    revoked: AtomicBool,
+}
+
+impl RevocableBorrow {
+    const_fn! {
+        /// Creates a new `RevocableBorrow`.
+        #[inline]
+        pub fn new() -> Self {
+            Self {
+                borrow: AtomicBorrow::new(),
+                revoked: AtomicBool::new(false),
+            }
+        }
+    }
+
+    /// Revokes `self`, making all future borrows fail.
+    #[inline]
+    pub fn revoke(&self) {
+        self.revoked.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns true if `self` has been revoked.
+    #[inline]
+    pub fn is_revoked(&self) -> bool {
+        self.revoked.load(Ordering::SeqCst)
+    }
+
+    /// Returns true if `self` is borrowed in any way.
+    #[inline]
+    pub fn is_borrowed(&self) -> bool {
+        self.borrow.is_borrowed()
+    }
+
+    /// Returns the current borrow state of `self`.
+    #[inline]
+    pub fn state(&self) -> BorrowState {
+        self.borrow.state()
+    }
+
+    /// Returns the number of shared references.
+    #[inline]
+    pub fn shared_count(&self) -> usize {
+        self.borrow.shared_count()
+    }
+
+    /// Tries to acquire a shared reference.
+    ///
+    /// Returns `true` if the reference was acquired, always `false` once revoked.
+    #[inline]
+    pub fn borrow(&self) -> bool {
+        if !self.borrow.borrow() {
+            return false;
+        }
+
+        // check after acquiring, so a borrow racing with `revoke` can't slip through
+        if self.is_revoked() {
+            self.borrow.release();
+            return false;
+        }
+
+        true
+    }
+
+    /// Tries to acquire a unique reference.
+    ///
+    /// Returns `true` if the reference was acquired, always `false` once revoked.
+    #[inline]
+    pub fn borrow_mut(&self) -> bool {
+        if !self.borrow.borrow_mut() {
+            return false;
+        }
+
+        if self.is_revoked() {
+            self.borrow.release_mut();
+            return false;
+        }
+
+        true
+    }
+
+    /// Releases a shared reference.
+    #[inline]
+    pub fn release(&self) {
+        self.borrow.release();
+    }
+
+    /// Releases a unique reference.
+    #[inline]
+    pub fn release_mut(&self) {
+        self.borrow.release_mut();
+    }
+}
@@ -0,0 +1,89 @@
+use std::{ops::Deref, sync::atomic::Ordering, thread::Scope};
+
+use crate::AtomicBorrow;
+
+/// A guard that releases a shared reference when dropped, which can be sent between
+/// threads.
+///
+/// Cloning the guard acquires another shared reference.
+pub struct SendSharedGuard<'a, T> {
+    data: &'a T,
+    borrow: &'a AtomicBorrow,
+}
+
+impl<'a, T> SendSharedGuard<'a, T> {
+    /// Tries to borrow the data.
+    ///
+    /// Since `data` is an immutable reference, it can't be uniquely borrowed
+    /// elsewhere, `borrow` merely tracks the reference.
+    #[inline]
+    pub fn try_new(data: &'a T, borrow: &'a AtomicBorrow) -> Option<Self> {
+        if borrow.borrow() {
+            Some(Self { data, borrow })
+        } else {
+            None
+        }
+    }
+
+    /// Gets the inner [`AtomicBorrow`].
+    #[inline]
+    pub fn get_borrow(&self) -> &'a AtomicBorrow {
+        self.borrow
+    }
+}
+
+impl<'a, T> Clone for SendSharedGuard<'a, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        // we already hold a shared reference, so the unique bit can't be set, increment
+        // directly instead of going through `borrow`, which may fail on a failpoint
+        let prev = self.borrow.counter_add(1, Ordering::Relaxed);
+
+        if prev & AtomicBorrow::SHARED_MASK == AtomicBorrow::SHARED_MASK {
+            self.borrow.counter_sub(1, Ordering::Relaxed);
+            panic!("borrow counter overflowed");
+        }
+
+        self.borrow.acquired();
+
+        Self {
+            data: self.data,
+            borrow: self.borrow,
+        }
+    }
+}
+
+impl<'a, T> Deref for SendSharedGuard<'a, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.data
+    }
+}
+
+impl<'a, T> Drop for SendSharedGuard<'a, T> {
+    #[inline]
+    fn drop(&mut self) {
+        self.borrow.release();
+    }
+}
+
+impl AtomicBorrow {
+    /// Spins until a shared reference can be acquired, then runs `f` in a
+    /// [`std::thread::scope`] with a [`SendSharedGuard`] of `data`.
+    ///
+    /// All threads spawned in the scope are joined before this returns, so the guard
+    /// outlives every thread it's sent to.
+    #[inline]
+    pub fn scope_with_shared<'env, T, R, F>(&'env self, data: &'env T, f: F) -> R
+    where
+        T: Sync,
+        F: for<'scope> FnOnce(&'scope Scope<'scope, 'env>, SendSharedGuard<'env, T>) -> R,
+    {
+        self.spin_borrow();
+        let guard = SendSharedGuard { data, borrow: self };
+
+        std::thread::scope(|scope| f(scope, guard))
+    }
+}
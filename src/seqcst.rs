@@ -0,0 +1,102 @@
+use std::sync::atomic::Ordering;
+
+use crate::AtomicBorrow;
+
+/// Variants of the borrow operations using [`Ordering::SeqCst`].
+///
+/// The default operations use acquire and release orderings, which only order accesses
+/// relative to the same `AtomicBorrow`. These variants additionally take part in the
+/// single total order of all `SeqCst` operations, which some algorithms need to reason
+/// about several atomics together, eg. "if my borrow succeeded before your flag store,
+/// you must see my flag". On x86-64 they compile to the same instructions, on weakly
+/// ordered targets like aarch64 they may need extra barriers.
+///
+/// Mixing them with the default operations on the same `AtomicBorrow` is fine, but only
+/// the `SeqCst` ones are part of the total order. They run the same hooks as the default
+/// operations, so blocking and async waiters are woken by them too.
+impl AtomicBorrow {
+    /// Like [`borrow`](Self::borrow), but using [`Ordering::SeqCst`].
+    #[inline]
+    pub fn borrow_seqcst(&self) -> bool {
+        #[cfg(feature = "test-failpoints")]
+        if self.failpoint() {
+            return false;
+        }
+
+        let prev = self.borrow.fetch_add(1, Ordering::SeqCst);
+
+        if prev & Self::SHARED_MASK == Self::SHARED_MASK {
+            self.borrow.fetch_sub(1, Ordering::Relaxed);
+            panic!("borrow counter overflowed");
+        }
+
+        if prev & Self::UNIQUE_MASK != 0 {
+            // like `borrow`, wake the waiters if the undo is what frees `self`
+            if self.borrow.fetch_sub(1, Ordering::SeqCst) == 1 {
+                self.released(0);
+            }
+
+            false
+        } else {
+            self.acquired();
+            true
+        }
+    }
+
+    /// Like [`borrow_mut`](Self::borrow_mut), but using [`Ordering::SeqCst`].
+    #[inline]
+    pub fn borrow_mut_seqcst(&self) -> bool {
+        #[cfg(feature = "test-failpoints")]
+        if self.failpoint() {
+            return false;
+        }
+
+        let acquired = self
+            .borrow
+            .compare_exchange(0, Self::UNIQUE_MASK, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok();
+
+        if acquired {
+            #[cfg(feature = "debug-backtrace")]
+            self.record_backtrace();
+
+            #[cfg(feature = "debug-owner")]
+            self.record_owner();
+
+            self.acquired();
+        }
+
+        acquired
+    }
+
+    /// Like [`release`](Self::release), but using [`Ordering::SeqCst`].
+    #[inline]
+    pub fn release_seqcst(&self) {
+        let prev = self.borrow.fetch_sub(1, Ordering::SeqCst);
+        debug_assert_ne!(
+            prev, 0,
+            "borrow counter underflow, this means you released more times than you borrowed"
+        );
+
+        self.released(prev - 1);
+    }
+
+    /// Like [`release_mut`](Self::release_mut), but using [`Ordering::SeqCst`].
+    #[inline]
+    pub fn release_mut_seqcst(&self) {
+        #[cfg(feature = "debug-backtrace")]
+        self.clear_backtrace();
+
+        #[cfg(feature = "debug-owner")]
+        self.clear_owner();
+
+        let prev = self.borrow.fetch_and(!Self::UNIQUE_MASK, Ordering::SeqCst);
+        debug_assert_ne!(
+            prev & Self::UNIQUE_MASK,
+            0,
+            "unique release of shared borrow"
+        );
+
+        self.released(prev & !Self::UNIQUE_MASK);
+    }
+}
@@ -0,0 +1,112 @@
+use std::sync::atomic::{compiler_fence, Ordering};
+
+use crate::AtomicBorrow;
+
+/// An [`AtomicBorrow`] for single-core use, e.g. shared between a thread and its signal
+/// handlers, or between bare-metal code and its interrupt handlers.
+///
+/// Every operation uses `Relaxed` atomics, only preventing compiler reordering with
+/// [`compiler_fence`] rather than emitting hardware fences, so they only synchronize
+/// with other operations on the same core.
+///
+/// The hooks of [`AtomicBorrow`] that wake blocked threads and futures take locks and
+/// allocate, which isn't allowed in a signal handler, so none of them run here. The
+/// inner [`AtomicBorrow`] isn't exposed, which means no thread or future can wait on a
+/// `SignalSafeBorrow` and miss its releases.
+#[derive(Debug, Default)]
+pub struct SignalSafeBorrow {
+    borrow: AtomicBorrow,
+}
+
+impl SignalSafeBorrow {
+    const_fn! {
+        /// Creates a new `SignalSafeBorrow`.
+        #[inline]
+        pub fn new() -> Self {
+            Self {
+                borrow: AtomicBorrow::new(),
+            }
+        }
+    }
+
+    /// Returns the number of shared references.
+    #[inline]
+    pub fn shared_count(&self) -> usize {
+        self.borrow.shared_count()
+    }
+
+    /// Returns true if there are any references.
+    #[inline]
+    pub fn is_borrowed(&self) -> bool {
+        self.borrow.is_borrowed()
+    }
+
+    /// Tries to acquire a shared reference without hardware fences.
+    ///
+    /// Returns `true` if the reference was acquired.
+    #[inline]
+    pub fn borrow(&self) -> bool {
+        let prev = self.borrow.borrow.fetch_add(1, Ordering::Relaxed);
+
+        if prev & AtomicBorrow::SHARED_MASK == AtomicBorrow::SHARED_MASK {
+            self.borrow.borrow.fetch_sub(1, Ordering::Relaxed);
+            panic!("borrow counter overflowed");
+        }
+
+        if prev & AtomicBorrow::UNIQUE_MASK != 0 {
+            self.borrow.borrow.fetch_sub(1, Ordering::Relaxed);
+            false
+        } else {
+            compiler_fence(Ordering::Acquire);
+            true
+        }
+    }
+
+    /// Tries to acquire a unique reference without hardware fences.
+    ///
+    /// Returns `true` if the reference was acquired.
+    #[inline]
+    pub fn borrow_mut(&self) -> bool {
+        let acquired = self
+            .borrow
+            .borrow
+            .compare_exchange(
+                0,
+                AtomicBorrow::UNIQUE_MASK,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            )
+            .is_ok();
+
+        if acquired {
+            compiler_fence(Ordering::Acquire);
+        }
+
+        acquired
+    }
+
+    /// Releases a shared reference without hardware fences.
+    #[inline]
+    pub fn release(&self) {
+        compiler_fence(Ordering::Release);
+
+        let prev = self.borrow.borrow.fetch_sub(1, Ordering::Relaxed);
+        debug_assert_ne!(prev, 0, "borrow counter underflow");
+    }
+
+    /// Releases a unique reference without hardware fences.
+    #[inline]
+    pub fn release_mut(&self) {
+        compiler_fence(Ordering::Release);
+
+        let prev = self
+            .borrow
+            .borrow
+            .fetch_and(!AtomicBorrow::UNIQUE_MASK, Ordering::Relaxed);
+        debug_assert_ne!(
+            prev & AtomicBorrow::UNIQUE_MASK,
+            0,
+            "unique release of shared borrow"
+        );
+    }
+}
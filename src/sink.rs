@@ -0,0 +1,85 @@
+use std::sync::Mutex;
+
+use crate::{AtomicBorrow, SharedGuard, UniqueGuard};
+
+/// A collection of pending releases, performed when [`flush`](Self::flush)ed or dropped.
+///
+/// Guards hand their release to a sink with [`SharedGuard::transfer_release_to`] and
+/// [`UniqueGuard::transfer_release_to`], a structured alternative to forgetting the guard
+/// and releasing manually.
+#[derive(Debug, Default)]
+pub struct ReleaseSink<'a> {
+    // (borrow, is unique)
+    pending: Mutex<Vec<(&'a AtomicBorrow, bool)>>,
+}
+
+impl<'a> ReleaseSink<'a> {
+    /// Creates a new empty `ReleaseSink`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    fn push(&self, borrow: &'a AtomicBorrow, unique: bool) {
+        let mut pending = self.pending.lock().unwrap_or_else(|err| err.into_inner());
+        pending.push((borrow, unique));
+    }
+
+    /// Returns the number of pending releases.
+    #[inline]
+    pub fn len(&self) -> usize {
+        let pending = self.pending.lock().unwrap_or_else(|err| err.into_inner());
+        pending.len()
+    }
+
+    /// Returns true if there are no pending releases.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Performs all pending releases.
+    #[inline]
+    pub fn flush(&self) {
+        let pending = {
+            let mut pending = self.pending.lock().unwrap_or_else(|err| err.into_inner());
+            std::mem::take(&mut *pending)
+        };
+
+        for (borrow, unique) in pending {
+            if unique {
+                borrow.release_mut();
+            } else {
+                borrow.release();
+            }
+        }
+    }
+}
+
+impl<'a> Drop for ReleaseSink<'a> {
+    #[inline]
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+impl<'a, T> SharedGuard<'a, T> {
+    /// Gives up access to the data, handing the release of the borrow to `target`.
+    #[inline]
+    pub fn transfer_release_to(self, target: &ReleaseSink<'a>) {
+        let borrow = self.borrow;
+        self.forget();
+        target.push(borrow, false);
+    }
+}
+
+impl<'a, T> UniqueGuard<'a, T> {
+    /// Gives up access to the data, handing the release of the borrow to `target`.
+    #[inline]
+    pub fn transfer_release_to(self, target: &ReleaseSink<'a>) {
+        let borrow = self.borrow;
+        self.forget();
+        target.push(borrow, true);
+    }
+}
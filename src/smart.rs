@@ -0,0 +1,127 @@
+use std::{
+    ops::Deref,
+    sync::atomic::{AtomicU64, AtomicU8, AtomicUsize, Ordering},
+    time::Instant,
+};
+
+use crate::AtomicBorrow;
+
+/// How a [`SmartBorrow`] waits for a contended unique reference.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WaitStrategy {
+    /// Spin, like [`AtomicBorrow::spin_borrow_mut`].
+    Spin,
+    /// Park the thread, like [`AtomicBorrow::blocking_borrow_mut_with`] without spinning.
+    Park,
+}
+
+/// An [`AtomicBorrow`] that picks whether to spin or park by measuring both.
+///
+/// The first [`SmartBorrow::PROBES`] contended acquisitions alternate between spinning
+/// and parking, after which the strategy with the lower total wait is used from then on.
+#[derive(Debug)]
+pub struct SmartBorrow {
+    borrow: AtomicBorrow,
+    strategy: AtomicU8,
+    probes: AtomicUsize,
+    // nanoseconds spent waiting while probing, indexed by strategy
+    waited: [AtomicU64; 2],
+}
+
+impl Default for SmartBorrow {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SmartBorrow {
+    /// The number of contended acquisitions measured before deciding.
+    pub const PROBES: usize = 16;
+
+    const PROBING: u8 = 0;
+    const SPIN: u8 = 1;
+    const PARK: u8 = 2;
+
+    const_fn! {
+        /// Creates a new `SmartBorrow`.
+        #[inline]
+        pub fn new() -> Self {
+            Self {
+                borrow: AtomicBorrow::new(),
+                strategy: AtomicU8::new(Self::PROBING),
+                probes: AtomicUsize::new(0),
+                waited: [AtomicU64::new(0), AtomicU64::new(0)],
+            }
+        }
+    }
+
+    /// Returns the chosen strategy, or `None` while still probing.
+    #[inline]
+    pub fn strategy(&self) -> Option<WaitStrategy> {
+        match self.strategy.load(Ordering::Relaxed) {
+            Self::SPIN => Some(WaitStrategy::Spin),
+            Self::PARK => Some(WaitStrategy::Park),
+            _ => None,
+        }
+    }
+
+    /// Blocks until a unique reference can be acquired, spinning or parking depending on
+    /// which has performed better.
+    #[inline]
+    pub fn smart_borrow_mut(&self) {
+        if self.borrow.borrow_mut() {
+            return;
+        }
+
+        match self.strategy() {
+            Some(strategy) => self.wait(strategy),
+            None => self.probe(),
+        }
+    }
+
+    #[inline]
+    fn wait(&self, strategy: WaitStrategy) {
+        match strategy {
+            WaitStrategy::Spin => self.borrow.spin_borrow_mut(),
+            WaitStrategy::Park => self.borrow.blocking_borrow_mut_with(0),
+        }
+    }
+
+    #[cold]
+    fn probe(&self) {
+        let probe = self.probes.fetch_add(1, Ordering::Relaxed);
+
+        // probes past the limit race with the decision, spinning is the safe default
+        if probe >= Self::PROBES {
+            self.wait(WaitStrategy::Spin);
+            return;
+        }
+
+        let strategy = match probe % 2 {
+            0 => WaitStrategy::Spin,
+            _ => WaitStrategy::Park,
+        };
+
+        let start = Instant::now();
+        self.wait(strategy);
+        let waited = start.elapsed().as_nanos() as u64;
+        self.waited[probe % 2].fetch_add(waited, Ordering::Relaxed);
+
+        if probe == Self::PROBES - 1 {
+            let spin = self.waited[0].load(Ordering::Relaxed);
+            let park = self.waited[1].load(Ordering::Relaxed);
+            let decision = if spin <= park { Self::SPIN } else { Self::PARK };
+            self.strategy.store(decision, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Deref for SmartBorrow {
+    type Target = AtomicBorrow;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.borrow
+    }
+}
@@ -0,0 +1,26 @@
+use std::sync::atomic::Ordering;
+
+use crate::AtomicBorrow;
+
+/// The state of an [`AtomicBorrow`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BorrowState {
+    /// Not borrowed.
+    Unborrowed,
+    /// Shared borrowed, with the number of shared references.
+    Shared(usize),
+    /// Uniquely borrowed.
+    Unique,
+}
+
+impl AtomicBorrow {
+    /// Returns the current state of `self`.
+    #[inline]
+    pub fn state(&self) -> BorrowState {
+        match self.borrow.load(Ordering::Acquire) {
+            0 => BorrowState::Unborrowed,
+            state if state & Self::UNIQUE_MASK != 0 => BorrowState::Unique,
+            shared => BorrowState::Shared(shared),
+        }
+    }
+}
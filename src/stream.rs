@@ -0,0 +1,50 @@
+use std::{
+    collections::VecDeque,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_util::Stream;
+
+use crate::UniqueGuard;
+
+impl<'a, T> UniqueGuard<'a, VecDeque<T>> {
+    /// Drains the queue as a [`Stream`], holding the unique reference until the stream
+    /// is dropped.
+    #[inline]
+    pub fn drain_stream(self) -> DrainStream<'a, T> {
+        DrainStream { guard: self }
+    }
+}
+
+/// A [`Stream`] draining a uniquely borrowed [`VecDeque`].
+///
+/// Created by [`UniqueGuard::drain_stream`].
+#[must_use = "streams do nothing unless polled"]
+pub struct DrainStream<'a, T> {
+    guard: UniqueGuard<'a, VecDeque<T>>,
+}
+
+impl<'a, T> DrainStream<'a, T> {
+    /// Stops draining, returning the guard of the remaining queue.
+    #[inline]
+    pub fn into_guard(self) -> UniqueGuard<'a, VecDeque<T>> {
+        self.guard
+    }
+}
+
+impl<'a, T> Unpin for DrainStream<'a, T> {}
+
+impl<'a, T> Stream for DrainStream<'a, T> {
+    type Item = T;
+
+    #[inline]
+    fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(self.guard.pop_front())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.guard.len(), Some(self.guard.len()))
+    }
+}
@@ -0,0 +1,121 @@
+use std::sync::atomic::Ordering;
+
+#[cfg(loom)]
+use loom::sync::atomic::{fence, AtomicBool};
+#[cfg(not(loom))]
+use std::sync::atomic::{fence, AtomicBool};
+
+use crate::AtomicBorrow;
+
+/// A borrow for a single writer and multiple readers.
+///
+/// Readers count themselves in an [`AtomicBorrow`], while the writer only raises a
+/// flag of its own and checks the count, like Dekker's algorithm. The writer never
+/// performs a read-modify-write, and never writes to the cache line the readers
+/// contend on, at the cost of a `SeqCst` fence on both sides.
+///
+/// This relies on there being a single writer: two threads acquiring through
+/// [`writer_borrow_mut`](Self::writer_borrow_mut) at once could both succeed, which is
+/// why it's `unsafe`.
+#[derive(Debug, Default)]
+pub struct SwmrBorrow {
+    readers: AtomicBorrow,
+    writing: AtomicBool,
+}
+
+impl SwmrBorrow {
+    const_fn! {
+        /// Creates a new `SwmrBorrow`.
+        #[inline]
+        pub fn new() -> Self {
+            Self {
+                readers: AtomicBorrow::new(),
+                writing: AtomicBool::new(false),
+            }
+        }
+    }
+
+    /// Returns the number of readers.
+    #[inline]
+    pub fn shared_count(&self) -> usize {
+        self.readers.shared_count()
+    }
+
+    /// Returns true if the writer holds a unique reference.
+    #[inline]
+    pub fn is_writing(&self) -> bool {
+        self.writing.load(Ordering::Relaxed)
+    }
+
+    /// Returns true if there are any references.
+    #[inline]
+    pub fn is_borrowed(&self) -> bool {
+        self.readers.is_borrowed() || self.is_writing()
+    }
+
+    /// Tries to acquire a shared reference as a reader.
+    ///
+    /// Returns `true` if the reference was acquired.
+    #[inline]
+    pub fn reader_borrow(&self) -> bool {
+        // the unique bit of `readers` is never set, this only fails on a failpoint
+        if !self.readers.borrow() {
+            return false;
+        }
+
+        // pairs with the fence in `writer_borrow_mut`, either the writer sees our count
+        // or we see its flag
+        fence(Ordering::SeqCst);
+
+        if self.writing.load(Ordering::Acquire) {
+            self.readers.release();
+            return false;
+        }
+
+        true
+    }
+
+    /// Releases a shared reference as a reader.
+    #[inline]
+    pub fn reader_release(&self) {
+        self.readers.release();
+    }
+
+    /// Tries to acquire a unique reference as the writer.
+    ///
+    /// Returns `true` if the reference was acquired.
+    ///
+    /// # Safety
+    /// Only one thread may act as the writer at a time, calls to this and
+    /// [`writer_release_mut`](Self::writer_release_mut) must not overlap between threads,
+    /// and this must not be called while the writer already holds the reference.
+    #[inline]
+    pub unsafe fn writer_borrow_mut(&self) -> bool {
+        // a plain store, the flag is only ever written by the writer
+        self.writing.store(true, Ordering::Relaxed);
+
+        // pairs with the fence in `reader_borrow`
+        fence(Ordering::SeqCst);
+
+        if self.readers.borrow.load(Ordering::Acquire) != 0 {
+            self.writing.store(false, Ordering::Release);
+            return false;
+        }
+
+        true
+    }
+
+    /// Releases a unique reference as the writer.
+    ///
+    /// # Safety
+    /// The writer must hold the reference, acquired with
+    /// [`writer_borrow_mut`](Self::writer_borrow_mut).
+    #[inline]
+    pub unsafe fn writer_release_mut(&self) {
+        debug_assert!(
+            self.is_writing(),
+            "writer release without a unique reference"
+        );
+        self.writing.store(false, Ordering::Release);
+    }
+}
@@ -0,0 +1,68 @@
+use crate::AtomicBorrow;
+
+impl AtomicBorrow {
+    /// Tries to uniquely borrow all of `borrows` at once.
+    ///
+    /// This is the prepare phase of a [`Transaction`]. The borrows are acquired in a
+    /// canonical order, by address, so overlapping transactions always contend on the
+    /// same borrow first instead of each holding part of what the other needs. Borrows
+    /// listed more than once are only acquired once.
+    ///
+    /// If any of them can't be borrowed, the ones already acquired are released again
+    /// and `None` is returned, so either all of `borrows` are held or none are.
+    #[inline]
+    pub fn transaction<'a>(borrows: &[&'a AtomicBorrow]) -> Option<Transaction<'a>> {
+        let mut borrows = borrows.to_vec();
+        borrows.sort_unstable_by_key(|borrow| *borrow as *const AtomicBorrow);
+        borrows.dedup_by(|a, b| std::ptr::eq(*a, *b));
+
+        for (i, borrow) in borrows.iter().enumerate() {
+            if !borrow.borrow_mut() {
+                // roll back in the reverse order
+                for borrow in borrows[..i].iter().rev() {
+                    borrow.release_mut();
+                }
+
+                return None;
+            }
+        }
+
+        Some(Transaction { borrows })
+    }
+}
+
+/// Unique borrows of several [`AtomicBorrow`]s, held until the transaction is committed.
+///
+/// Created by [`AtomicBorrow::transaction`]. Dropping it commits it.
+#[derive(Debug)]
+#[must_use = "dropping a transaction commits it immediately"]
+pub struct Transaction<'a> {
+    borrows: Vec<&'a AtomicBorrow>,
+}
+
+impl<'a> Transaction<'a> {
+    /// Gets the borrows held by the transaction, in the order they were acquired.
+    #[inline]
+    pub fn borrows(&self) -> &[&'a AtomicBorrow] {
+        &self.borrows
+    }
+
+    /// Returns `true` if `borrow` is held by the transaction.
+    #[inline]
+    pub fn contains(&self, borrow: &AtomicBorrow) -> bool {
+        self.borrows.iter().any(|b| std::ptr::eq(*b, borrow))
+    }
+
+    /// Commits the transaction, releasing all borrows.
+    #[inline]
+    pub fn commit(self) {}
+}
+
+impl<'a> Drop for Transaction<'a> {
+    #[inline]
+    fn drop(&mut self) {
+        for borrow in self.borrows.iter().rev() {
+            borrow.release_mut();
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::{cell::Cell, sync::atomic::Ordering};
+
+/// A non atomic stand-in for [`AtomicUsize`](std::sync::atomic::AtomicUsize), used by
+/// the `single-threaded` feature.
+///
+/// The orderings are ignored, since there is only ever one thread. `Cell` makes it, and
+/// every type containing it, `!Sync`.
+#[repr(transparent)]
+#[derive(Debug, Default)]
+pub(crate) struct AtomicUsize {
+    value: Cell<usize>,
+}
+
+#[allow(dead_code)]
+impl AtomicUsize {
+    #[inline]
+    pub(crate) const fn new(value: usize) -> Self {
+        Self {
+            value: Cell::new(value),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn load(&self, _: Ordering) -> usize {
+        self.value.get()
+    }
+
+    #[inline]
+    pub(crate) fn store(&self, value: usize, _: Ordering) {
+        self.value.set(value);
+    }
+
+    #[inline]
+    pub(crate) fn swap(&self, value: usize, _: Ordering) -> usize {
+        self.value.replace(value)
+    }
+
+    #[inline]
+    fn update(&self, f: impl FnOnce(usize) -> usize) -> usize {
+        let prev = self.value.get();
+        self.value.set(f(prev));
+        prev
+    }
+
+    #[inline]
+    pub(crate) fn fetch_add(&self, value: usize, _: Ordering) -> usize {
+        self.update(|prev| prev.wrapping_add(value))
+    }
+
+    #[inline]
+    pub(crate) fn fetch_sub(&self, value: usize, _: Ordering) -> usize {
+        self.update(|prev| prev.wrapping_sub(value))
+    }
+
+    #[inline]
+    pub(crate) fn fetch_and(&self, value: usize, _: Ordering) -> usize {
+        self.update(|prev| prev & value)
+    }
+
+    #[inline]
+    pub(crate) fn fetch_or(&self, value: usize, _: Ordering) -> usize {
+        self.update(|prev| prev | value)
+    }
+
+    #[inline]
+    pub(crate) fn compare_exchange(
+        &self,
+        current: usize,
+        new: usize,
+        _: Ordering,
+        _: Ordering,
+    ) -> Result<usize, usize> {
+        let prev = self.value.get();
+
+        if prev == current {
+            self.value.set(new);
+            Ok(prev)
+        } else {
+            Err(prev)
+        }
+    }
+
+    #[inline]
+    pub(crate) fn compare_exchange_weak(
+        &self,
+        current: usize,
+        new: usize,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<usize, usize> {
+        self.compare_exchange(current, new, success, failure)
+    }
+}
@@ -0,0 +1,55 @@
+use std::{
+    mem,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::AtomicBorrow;
+
+pub(crate) const BUCKETS: usize = 64;
+
+/// Returns the bucket `borrow` is hashed into, out of [`BUCKETS`].
+#[inline]
+pub(crate) fn bucket_of(borrow: &AtomicBorrow) -> usize {
+    borrow as *const AtomicBorrow as usize / mem::size_of::<AtomicBorrow>() % BUCKETS
+}
+
+/// The number of waiters on each [`AtomicBorrow`], hashed by address into a fixed
+/// number of buckets.
+///
+/// Releases check the bucket of their borrow before doing anything expensive, so they
+/// only pay for a wakeup when a borrow in the same bucket has waiters.
+pub(crate) struct WaiterCounts {
+    buckets: [AtomicUsize; BUCKETS],
+}
+
+impl WaiterCounts {
+    pub(crate) const fn new() -> Self {
+        Self {
+            buckets: [const { AtomicUsize::new(0) }; BUCKETS],
+        }
+    }
+
+    #[inline]
+    fn bucket(&self, borrow: &AtomicBorrow) -> &AtomicUsize {
+        &self.buckets[bucket_of(borrow)]
+    }
+
+    /// Counts a waiter on `borrow`, which must check the state of `borrow` afterwards
+    /// with `SeqCst`, see [`AtomicBorrow::RELEASE`].
+    #[inline]
+    pub(crate) fn add(&self, borrow: &AtomicBorrow) {
+        self.bucket(borrow).fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Removes a waiter counted by [`add`](Self::add).
+    #[inline]
+    pub(crate) fn remove(&self, borrow: &AtomicBorrow) {
+        self.bucket(borrow).fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if `borrow` might have waiters.
+    #[inline]
+    pub(crate) fn may_have_waiters(&self, borrow: &AtomicBorrow) -> bool {
+        self.bucket(borrow).load(Ordering::SeqCst) != 0
+    }
+}
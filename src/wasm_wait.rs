@@ -0,0 +1,61 @@
+use std::{arch::wasm32, sync::atomic::Ordering};
+
+use crate::AtomicBorrow;
+
+impl AtomicBorrow {
+    #[inline]
+    fn wait_ptr(&self) -> *mut i32 {
+        self.borrow.as_ptr() as *mut i32
+    }
+
+    /// Blocks the current worker until the counter no longer holds `state`, or until it
+    /// is notified.
+    #[cold]
+    fn wait_while(&self, state: usize) {
+        // SAFETY: the counter is a valid, aligned `i32` in shared memory on `wasm32`.
+        unsafe {
+            wasm32::memory_atomic_wait32(self.wait_ptr(), state as i32, -1);
+        }
+    }
+
+    /// Wakes all workers blocked in [`Self::wait_borrow`] or [`Self::wait_borrow_mut`].
+    #[inline]
+    pub(crate) fn notify_waiters(&self) {
+        // SAFETY: the counter is a valid, aligned `i32` in shared memory on `wasm32`.
+        unsafe {
+            wasm32::memory_atomic_notify(self.wait_ptr(), u32::MAX);
+        }
+    }
+
+    /// Blocks until a shared reference can be acquired.
+    ///
+    /// Sleeps with `Atomics.wait` instead of spinning, since [`std::thread::yield_now`]
+    /// does nothing in a WASM worker. The browser main thread isn't allowed to block, so
+    /// this traps there.
+    #[inline]
+    pub fn wait_borrow(&self) {
+        while !self.borrow() {
+            let state = self.borrow.load(Ordering::Relaxed);
+
+            if state & Self::UNIQUE_MASK != 0 {
+                self.wait_while(state);
+            }
+        }
+    }
+
+    /// Blocks until a unique reference can be acquired.
+    ///
+    /// Sleeps with `Atomics.wait` instead of spinning, since [`std::thread::yield_now`]
+    /// does nothing in a WASM worker. The browser main thread isn't allowed to block, so
+    /// this traps there.
+    #[inline]
+    pub fn wait_borrow_mut(&self) {
+        while !self.borrow_mut() {
+            let state = self.borrow.load(Ordering::Relaxed);
+
+            if state != 0 {
+                self.wait_while(state);
+            }
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::{
+        atomic::{self, AtomicUsize, Ordering},
+        Mutex, OnceLock,
+    },
+    task::{Context, Poll, Waker},
+};
+
+use futures_util::Stream;
+
+use crate::{AtomicBorrow, BorrowState};
+
+/// The number of wakers currently registered, used to skip the registry when empty.
+static WATCHERS: AtomicUsize = AtomicUsize::new(0);
+
+/// Wakers of [`StateStream`]s keyed by the address of the [`AtomicBorrow`], with the id
+/// of the stream that registered them.
+type Registry = Mutex<HashMap<usize, Vec<(usize, Waker)>>>;
+
+fn registry() -> &'static Registry {
+    static REGISTRY: OnceLock<Registry> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+impl AtomicBorrow {
+    #[inline]
+    fn watch_key(&self) -> usize {
+        self as *const Self as usize
+    }
+
+    /// Wakes all [`StateStream`]s of `self`, must be called after every transition.
+    #[inline]
+    pub(crate) fn state_changed(&self) {
+        // pairs with the fence in `StateStream::poll_next`
+        atomic::fence(Ordering::SeqCst);
+
+        if WATCHERS.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+
+        self.wake_watchers();
+    }
+
+    /// Returns true if a [`StateStream`] of `self` has a waker registered.
+    #[cfg(test)]
+    pub(crate) fn has_watchers(&self) -> bool {
+        registry().lock().unwrap().contains_key(&self.watch_key())
+    }
+
+    #[cold]
+    fn wake_watchers(&self) {
+        let wakers = registry().lock().unwrap().remove(&self.watch_key());
+
+        for (_, waker) in wakers.into_iter().flatten() {
+            WATCHERS.fetch_sub(1, Ordering::Relaxed);
+            waker.wake();
+        }
+    }
+
+    /// Returns a [`Stream`] of the states of `self`, yielding the current state first and
+    /// then whenever it changes.
+    ///
+    /// The stream only yields the latest state, so states that change again before the
+    /// stream is polled are skipped, and it never ends. The `state-stream` feature makes
+    /// every acquisition and release of every `AtomicBorrow` cost a fence to check for
+    /// streams, so only enable it where that is acceptable.
+    #[inline]
+    pub fn state_stream(&self) -> StateStream<'_> {
+        StateStream {
+            borrow: self,
+            last: None,
+            id: None,
+        }
+    }
+}
+
+/// A [`Stream`] of the states of an [`AtomicBorrow`].
+///
+/// Created by [`AtomicBorrow::state_stream`].
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct StateStream<'a> {
+    borrow: &'a AtomicBorrow,
+    last: Option<BorrowState>,
+    // assigned on the first registration, streams may move between polls
+    id: Option<usize>,
+}
+
+impl<'a> StateStream<'a> {
+    /// Returns the current state if it differs from the last one yielded.
+    #[inline]
+    fn changed(&mut self) -> Option<BorrowState> {
+        let state = self.borrow.state();
+
+        if self.last == Some(state) {
+            return None;
+        }
+
+        self.last = Some(state);
+        Some(state)
+    }
+
+    fn register(&mut self, waker: &Waker) {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+        let id = *self
+            .id
+            .get_or_insert_with(|| NEXT_ID.fetch_add(1, Ordering::Relaxed));
+
+        let mut registry = registry().lock().unwrap();
+        let wakers = registry.entry(self.borrow.watch_key()).or_default();
+
+        match wakers.iter_mut().find(|(i, _)| *i == id) {
+            Some((_, w)) => w.clone_from(waker),
+            None => {
+                wakers.push((id, waker.clone()));
+                WATCHERS.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+    }
+
+    /// Removes the waker, if it wasn't woken already.
+    fn deregister(&mut self) {
+        let Some(id) = self.id else {
+            return;
+        };
+
+        let key = self.borrow.watch_key();
+        let mut registry = registry().lock().unwrap();
+
+        if let Some(wakers) = registry.get_mut(&key) {
+            if let Some(index) = wakers.iter().position(|(i, _)| *i == id) {
+                wakers.swap_remove(index);
+                WATCHERS.fetch_sub(1, Ordering::Relaxed);
+            }
+
+            if wakers.is_empty() {
+                registry.remove(&key);
+            }
+        }
+    }
+}
+
+impl<'a> Stream for StateStream<'a> {
+    type Item = BorrowState;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(state) = self.changed() {
+            return Poll::Ready(Some(state));
+        }
+
+        self.register(cx.waker());
+
+        // check again in case the state changed before the waker was registered
+        atomic::fence(Ordering::SeqCst);
+
+        match self.changed() {
+            Some(state) => Poll::Ready(Some(state)),
+            None => Poll::Pending,
+        }
+    }
+}
+
+impl<'a> Drop for StateStream<'a> {
+    #[inline]
+    fn drop(&mut self) {
+        self.deregister();
+    }
+}
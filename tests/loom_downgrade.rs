@@ -0,0 +1,52 @@
+//! Model checks that no writer gets in between the write and read of
+//! `AtomicBorrow::write_then_read`.
+//!
+//! Run with `RUSTFLAGS="--cfg loom" cargo test --test loom_downgrade --release`.
+
+#![cfg(loom)]
+
+use atomic_borrow::AtomicBorrow;
+use std::cell::UnsafeCell;
+
+use loom::{sync::Arc, thread};
+
+// not loom's `UnsafeCell`, since `write_then_read` takes a raw pointer up front, so
+// this only checks the values seen in every interleaving
+struct Cell {
+    borrow: AtomicBorrow,
+    data: UnsafeCell<usize>,
+}
+
+unsafe impl Sync for Cell {}
+
+#[test]
+fn write_then_read() {
+    loom::model(|| {
+        let cell = Arc::new(Cell {
+            borrow: AtomicBorrow::new(),
+            data: UnsafeCell::new(0),
+        });
+
+        let writer = {
+            let cell = cell.clone();
+            thread::spawn(move || {
+                if cell.borrow.borrow_mut() {
+                    unsafe { *cell.data.get() = 2 };
+                    cell.borrow.release_mut();
+                }
+            })
+        };
+
+        let data = cell.data.get();
+        let read = unsafe {
+            cell.borrow
+                .write_then_read(data, |data| *data = 1, |data| *data)
+        };
+
+        if let Some(read) = read {
+            assert_eq!(read, 1);
+        }
+
+        writer.join().unwrap();
+    });
+}
@@ -0,0 +1,67 @@
+//! Model checks that a write under a `UniqueGuard` happens before a read under a
+//! `SharedGuard` acquired after the unique guard was released.
+//!
+//! Run with `RUSTFLAGS="--cfg loom" cargo test --test loom_happens_before --release`.
+
+#![cfg(loom)]
+
+use atomic_borrow::{AtomicBorrow, SharedGuard, UniqueGuard};
+use loom::{
+    cell::UnsafeCell,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+struct Cell {
+    borrow: AtomicBorrow,
+    data: UnsafeCell<usize>,
+    // relaxed, so it can't establish any ordering of its own
+    written: AtomicBool,
+}
+
+unsafe impl Sync for Cell {}
+
+#[test]
+fn unique_release_happens_before_shared_borrow() {
+    loom::model(|| {
+        let cell = Arc::new(Cell {
+            borrow: AtomicBorrow::new(),
+            data: UnsafeCell::new(0),
+            written: AtomicBool::new(false),
+        });
+
+        let writer = {
+            let cell = cell.clone();
+            thread::spawn(move || {
+                while !cell.borrow.borrow_mut() {
+                    thread::yield_now();
+                }
+
+                cell.data.with_mut(|data| {
+                    let mut guard = UniqueGuard::new(unsafe { &mut *data }, &cell.borrow);
+                    *guard = 1;
+                });
+
+                cell.written.store(true, Ordering::Relaxed);
+            })
+        };
+
+        if cell.borrow.borrow() {
+            let written = cell.written.load(Ordering::Relaxed);
+
+            cell.data.with(|data| {
+                let guard = SharedGuard::new(unsafe { &*data }, &cell.borrow);
+
+                // once the write is done, acquiring the borrow must make it visible
+                if written {
+                    assert_eq!(*guard, 1);
+                }
+            });
+        }
+
+        writer.join().unwrap();
+    });
+}
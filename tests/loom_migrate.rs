@@ -0,0 +1,46 @@
+//! Model checks `AtomicBorrow::migrate_state` while `from` is changing.
+//!
+//! Run with `RUSTFLAGS="--cfg loom" cargo test --test loom_migrate --release`.
+
+#![cfg(loom)]
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use atomic_borrow::AtomicBorrow;
+use loom::{sync::Arc, thread};
+
+#[test]
+fn migrate_while_changing() {
+    static FAILED: AtomicBool = AtomicBool::new(false);
+    static MIGRATED: AtomicBool = AtomicBool::new(false);
+
+    loom::model(|| {
+        let from = Arc::new(AtomicBorrow::new());
+        let to = AtomicBorrow::new();
+
+        let reader = {
+            let from = from.clone();
+            thread::spawn(move || {
+                assert!(from.borrow());
+                from.release();
+            })
+        };
+
+        match AtomicBorrow::migrate_state(&from, &to) {
+            Ok(()) => {
+                // the copy is a state `from` had, either free or shared once
+                assert!(to.shared_count() <= 1);
+                MIGRATED.store(true, Ordering::Relaxed);
+            }
+            Err(()) => {
+                assert!(!to.is_borrowed());
+                FAILED.store(true, Ordering::Relaxed);
+            }
+        }
+
+        reader.join().unwrap();
+    });
+
+    assert!(FAILED.load(Ordering::Relaxed));
+    assert!(MIGRATED.load(Ordering::Relaxed));
+}
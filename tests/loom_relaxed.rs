@@ -0,0 +1,70 @@
+//! Model checks that `RelaxedBorrow` is only sound when paired with external fences.
+//!
+//! Run with `RUSTFLAGS="--cfg loom" cargo test --test loom_relaxed --release`.
+
+#![cfg(loom)]
+
+use atomic_borrow::RelaxedBorrow;
+use loom::{
+    cell::UnsafeCell,
+    sync::{
+        atomic::{fence, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+struct Cell {
+    borrow: RelaxedBorrow,
+    data: UnsafeCell<usize>,
+}
+
+unsafe impl Sync for Cell {}
+
+fn increment(cell: &Cell, fences: bool) {
+    while !cell.borrow.borrow_mut() {
+        thread::yield_now();
+    }
+
+    if fences {
+        fence(Ordering::Acquire);
+    }
+
+    cell.data.with_mut(|data| unsafe { *data += 1 });
+
+    if fences {
+        fence(Ordering::Release);
+    }
+
+    cell.borrow.release_mut();
+}
+
+fn model(fences: bool) {
+    loom::model(move || {
+        let cell = Arc::new(Cell {
+            borrow: RelaxedBorrow::new(),
+            data: UnsafeCell::new(0),
+        });
+
+        let writer = {
+            let cell = cell.clone();
+            thread::spawn(move || increment(&cell, fences))
+        };
+
+        increment(&cell, fences);
+        writer.join().unwrap();
+
+        assert_eq!(cell.data.with(|data| unsafe { *data }), 2);
+    });
+}
+
+#[test]
+fn with_fences() {
+    model(true);
+}
+
+#[test]
+#[should_panic(expected = "Causality violation")]
+fn without_fences() {
+    model(false);
+}
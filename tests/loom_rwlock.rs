@@ -0,0 +1,95 @@
+//! Model checks a minimal `RwLock` built on `AtomicBorrow`.
+//!
+//! Run with `RUSTFLAGS="--cfg loom" cargo test --test loom_rwlock --release`.
+
+#![cfg(loom)]
+
+use atomic_borrow::AtomicBorrow;
+use loom::{cell::UnsafeCell, sync::Arc, thread};
+
+struct RwLock<T> {
+    borrow: AtomicBorrow,
+    data: UnsafeCell<T>,
+}
+
+unsafe impl<T: Send + Sync> Sync for RwLock<T> {}
+
+impl<T> RwLock<T> {
+    fn new(data: T) -> Self {
+        Self {
+            borrow: AtomicBorrow::new(),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    fn try_read<R>(&self, f: impl FnOnce(&T) -> R) -> Option<R> {
+        if !self.borrow.borrow() {
+            return None;
+        }
+
+        let result = self.data.with(|data| f(unsafe { &*data }));
+        self.borrow.release();
+        Some(result)
+    }
+
+    fn write<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        while !self.borrow.borrow_mut() {
+            thread::yield_now();
+        }
+
+        let result = self.data.with_mut(|data| f(unsafe { &mut *data }));
+        self.borrow.release_mut();
+        result
+    }
+}
+
+#[test]
+fn readers_and_writer() {
+    let mut builder = loom::model::Builder::new();
+    builder.preemption_bound = Some(3);
+
+    builder.check(|| {
+        let lock = Arc::new(RwLock::new((0, 0)));
+
+        let readers = (0..2)
+            .map(|_| {
+                let lock = lock.clone();
+                thread::spawn(move || {
+                    // the writer updates both fields together, so they never differ
+                    if let Some((a, b)) = lock.try_read(|data| *data) {
+                        assert_eq!(a, b);
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        lock.write(|data| {
+            data.0 += 1;
+            data.1 += 1;
+        });
+
+        for reader in readers {
+            reader.join().unwrap();
+        }
+
+        assert_eq!(lock.try_read(|data| *data), Some((1, 1)));
+        assert!(!lock.borrow.is_borrowed());
+    });
+}
+
+#[test]
+fn writers() {
+    loom::model(|| {
+        let lock = Arc::new(RwLock::new(0));
+
+        let writer = {
+            let lock = lock.clone();
+            thread::spawn(move || lock.write(|data| *data += 1))
+        };
+
+        lock.write(|data| *data += 1);
+        writer.join().unwrap();
+
+        assert_eq!(lock.try_read(|data| *data), Some(2));
+    });
+}
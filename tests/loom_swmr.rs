@@ -0,0 +1,46 @@
+//! Model checks that the fences of `SwmrBorrow` keep readers and the writer apart.
+//!
+//! Run with `RUSTFLAGS="--cfg loom" cargo test --test loom_swmr --release`.
+
+#![cfg(loom)]
+
+use atomic_borrow::SwmrBorrow;
+use loom::{cell::UnsafeCell, sync::Arc, thread};
+
+struct Cell {
+    borrow: SwmrBorrow,
+    data: UnsafeCell<usize>,
+}
+
+unsafe impl Sync for Cell {}
+
+#[test]
+fn swmr_exclusion() {
+    loom::model(|| {
+        let cell = Arc::new(Cell {
+            borrow: SwmrBorrow::new(),
+            data: UnsafeCell::new(0),
+        });
+
+        let writer = {
+            let cell = cell.clone();
+            thread::spawn(move || {
+                // SAFETY: this is the only writer
+                if unsafe { cell.borrow.writer_borrow_mut() } {
+                    cell.data.with_mut(|data| unsafe { *data += 1 });
+                    // SAFETY: the writer holds the reference
+                    unsafe { cell.borrow.writer_release_mut() };
+                }
+            })
+        };
+
+        if cell.borrow.reader_borrow() {
+            let data = cell.data.with(|data| unsafe { *data });
+            assert!(data <= 1);
+            cell.borrow.reader_release();
+        }
+
+        writer.join().unwrap();
+        assert!(!cell.borrow.is_borrowed());
+    });
+}
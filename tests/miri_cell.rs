@@ -0,0 +1,119 @@
+//! Aliasing checks for `AtomicRefCell` under Miri's Stacked Borrows model.
+//!
+//! Run with `cargo +nightly miri test --test miri_cell`. The tests also run natively,
+//! but only Miri catches a guard handing out a reference that aliases another one.
+
+use atomic_borrow::{
+    AtomicRefCell, BorrowState, MappedUniqueGuard, OwnedSharedGuard, OwnedUniqueGuard, SharedGuard,
+    UniqueGuard,
+};
+
+#[test]
+fn cell_shared_guards_coexist() {
+    let cell = AtomicRefCell::new([1u32, 2, 3, 4]);
+
+    let a = cell.borrow();
+    let b = cell.try_borrow().unwrap();
+    let first = &a[0];
+    let last = &b[3];
+    assert!(cell.try_borrow_mut().is_err());
+    assert_eq!(*first + *last, 5);
+    drop((a, b));
+
+    assert_eq!(cell.state(), BorrowState::Unborrowed);
+}
+
+#[test]
+fn cell_unique_guard_writes() {
+    let cell = AtomicRefCell::new([0u32; 4]);
+
+    let mut guard = cell.borrow_mut();
+    let inner = &mut guard[1];
+    *inner = 1;
+    guard[2] = 2;
+    assert!(cell.try_borrow().is_err());
+    drop(guard);
+
+    // a new unique guard after the old one is gone retags the value
+    let mut guard = cell.borrow_mut();
+    guard[3] = 3;
+    drop(guard);
+
+    assert_eq!(*cell.borrow(), [0, 1, 2, 3]);
+}
+
+#[test]
+fn cell_mapped_guards() {
+    let cell = AtomicRefCell::new((1u32, vec![2u32, 3]));
+
+    let guard = UniqueGuard::map(cell.borrow_mut(), |(_, vec)| vec.as_mut_slice());
+    let mut guard = MappedUniqueGuard::map(guard, |slice| &mut slice[1]);
+    *guard = 4;
+    drop(guard);
+
+    let a = SharedGuard::map(cell.borrow(), |(a, _)| a);
+    let b = SharedGuard::map(cell.borrow(), |(_, vec)| vec.as_slice());
+    assert_eq!(*a + b[1], 5);
+    drop((a, b));
+
+    assert_eq!(cell.into_inner(), (1, vec![2, 4]));
+}
+
+#[test]
+fn cell_raw_pointer_while_unborrowed() {
+    let cell = AtomicRefCell::new(0u32);
+
+    // SAFETY: nothing is borrowed, so the write doesn't alias a guard
+    unsafe { *cell.as_ptr() = 1 };
+
+    let guard = cell.borrow();
+    assert_eq!(*guard, 1);
+    drop(guard);
+
+    *cell.borrow_mut() += 1;
+    assert_eq!(cell.into_inner(), 2);
+}
+
+#[test]
+#[cfg(not(feature = "single-threaded"))]
+fn cell_threads() {
+    let cell = AtomicRefCell::new(0u32);
+
+    std::thread::scope(|s| {
+        for _ in 0..2 {
+            s.spawn(|| loop {
+                if let Ok(mut guard) = cell.try_borrow_mut() {
+                    *guard += 1;
+                    break;
+                }
+
+                std::thread::yield_now();
+            });
+        }
+
+        s.spawn(|| {
+            if let Ok(guard) = cell.try_borrow() {
+                assert!(*guard <= 2);
+            }
+        });
+    });
+
+    assert_eq!(cell.into_inner(), 2);
+}
+
+#[test]
+fn cell_owned_guards() {
+    let shared = OwnedSharedGuard::new(5u32);
+    let cell = shared.get_cell().clone();
+    let other = OwnedSharedGuard::try_new(cell.clone()).unwrap();
+    assert_eq!(*shared + *other, 10);
+    assert!(OwnedUniqueGuard::try_new(cell.clone()).is_none());
+    drop((shared, other));
+
+    let mut unique = OwnedUniqueGuard::try_new(cell.clone()).unwrap();
+    *unique += 1;
+    assert!(cell.try_borrow().is_err());
+    drop(unique);
+
+    assert_eq!(*cell.borrow(), 6);
+}
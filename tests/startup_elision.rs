@@ -0,0 +1,49 @@
+//! The startup fast path is global, so it gets its own test binary instead of racing
+//! the other tests.
+
+#![cfg(all(feature = "startup-elision", not(feature = "single-threaded")))]
+
+use std::sync::Arc;
+
+use atomic_borrow::AtomicBorrow;
+
+#[test]
+fn single_threaded_startup() {
+    assert!(!AtomicBorrow::is_single_threaded());
+
+    // SAFETY: this is the only test in the binary, so no other thread touches a borrow
+    unsafe { AtomicBorrow::assume_single_threaded() };
+    assert!(AtomicBorrow::is_single_threaded());
+
+    let borrow = Arc::new(AtomicBorrow::new());
+    assert!(borrow.borrow());
+    assert!(borrow.borrow());
+    assert!(!borrow.borrow_mut());
+    assert_eq!(borrow.shared_count(), 2);
+    borrow.release();
+    borrow.release();
+
+    assert_eq!(borrow.checked_borrow(), Ok(()));
+    assert!(borrow.checked_borrow_mut().is_err());
+    borrow.release();
+
+    assert!(borrow.borrow_mut());
+    assert!(!borrow.borrow());
+    assert!(!borrow.borrow_mut());
+    assert!(borrow.is_borrowed());
+
+    let worker = AtomicBorrow::spawn({
+        let borrow = borrow.clone();
+        move || {
+            assert!(!AtomicBorrow::is_single_threaded());
+            // the unique borrow taken on the fast path is visible here
+            assert!(!borrow.borrow());
+            assert!(borrow.is_borrowed());
+        }
+    });
+    worker.join().unwrap();
+
+    assert!(!AtomicBorrow::is_single_threaded());
+    borrow.release_mut();
+    assert!(!borrow.is_borrowed());
+}
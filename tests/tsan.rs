@@ -0,0 +1,62 @@
+//! A reader/writer workload for ThreadSanitizer.
+//!
+//! Run with
+//! `RUSTFLAGS="-Zsanitizer=thread" cargo +nightly test --test tsan -Zbuild-std --target x86_64-unknown-linux-gnu`.
+
+#![cfg(not(feature = "single-threaded"))]
+
+use std::cell::UnsafeCell;
+
+use atomic_borrow::{AtomicBorrow, SharedGuard, UniqueGuard};
+
+struct RwCell<T> {
+    borrow: AtomicBorrow,
+    data: UnsafeCell<T>,
+}
+
+unsafe impl<T: Send + Sync> Sync for RwCell<T> {}
+
+impl<T> RwCell<T> {
+    fn read(&self) -> Option<SharedGuard<'_, T>> {
+        unsafe { SharedGuard::try_new(self.data.get(), &self.borrow) }
+    }
+
+    fn write(&self) -> UniqueGuard<'_, T> {
+        unsafe { UniqueGuard::spin(self.data.get(), &self.borrow) }
+    }
+}
+
+#[test]
+fn readers_and_writers() {
+    let cell = RwCell {
+        borrow: AtomicBorrow::new(),
+        data: UnsafeCell::new([0usize; 16]),
+    };
+
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                for _ in 0..1000 {
+                    if let Some(data) = cell.read() {
+                        // writers update every element together, so they never differ
+                        assert!(data.iter().all(|&x| x == data[0]));
+                    }
+                }
+            });
+        }
+
+        for _ in 0..2 {
+            s.spawn(|| {
+                for _ in 0..1000 {
+                    let mut data = cell.write();
+                    for x in data.iter_mut() {
+                        *x += 1;
+                    }
+                }
+            });
+        }
+    });
+
+    assert_eq!(cell.read().unwrap()[0], 2000);
+    assert!(!cell.borrow.is_borrowed());
+}
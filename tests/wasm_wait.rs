@@ -0,0 +1,73 @@
+//! Checks that a worker blocked in `Atomics.wait` is woken by a release.
+//!
+//! Run with
+//! `RUSTFLAGS="-C target-feature=+atomics,+bulk-memory" cargo +nightly test --test wasm_wait --features wasm-atomics --target wasm32-wasip1-threads`
+//! and a runner that supports threads, eg. `wasmtime -W threads=y -S threads=y`.
+
+#![cfg(all(
+    feature = "wasm-atomics",
+    target_arch = "wasm32",
+    target_feature = "atomics",
+    not(feature = "single-threaded")
+))]
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use atomic_borrow::AtomicBorrow;
+
+#[test]
+fn release_wakes_waiter() {
+    let borrow = Arc::new(AtomicBorrow::new());
+    let woken = Arc::new(AtomicBool::new(false));
+    assert!(borrow.borrow_mut());
+
+    let worker = thread::spawn({
+        let borrow = borrow.clone();
+        let woken = woken.clone();
+        move || {
+            borrow.wait_borrow_mut();
+            woken.store(true, Ordering::Release);
+            borrow.release_mut();
+        }
+    });
+
+    thread::sleep(Duration::from_millis(50));
+    assert!(!woken.load(Ordering::Acquire));
+
+    borrow.release_mut();
+    worker.join().unwrap();
+    assert!(woken.load(Ordering::Acquire));
+    assert!(!borrow.is_borrowed());
+}
+
+#[test]
+fn release_wakes_shared_waiters() {
+    let borrow = Arc::new(AtomicBorrow::new());
+    assert!(borrow.borrow_mut());
+
+    let workers: Vec<_> = (0..2)
+        .map(|_| {
+            let borrow = borrow.clone();
+            thread::spawn(move || {
+                borrow.wait_borrow();
+                borrow.release();
+            })
+        })
+        .collect();
+
+    thread::sleep(Duration::from_millis(50));
+    borrow.release_mut();
+
+    for worker in workers {
+        worker.join().unwrap();
+    }
+
+    assert!(!borrow.is_borrowed());
+}